@@ -0,0 +1,240 @@
+use crate::Exec;
+use pkpass::{sign::VerifyMode, Pass};
+use serde_json::Value;
+use std::{
+	collections::{BTreeMap, BTreeSet},
+	fmt,
+	fs::File,
+	path::PathBuf,
+};
+use yansi::Paint;
+
+#[derive(clap::Args)]
+pub(crate) struct Args {
+	/// Pass before the change (e.g. old.pkpass)
+	old: PathBuf,
+
+	/// Pass after the change (e.g. new.pkpass)
+	new: PathBuf,
+}
+
+impl Exec for Args {
+	fn run(self) -> Result<(), Box<dyn std::error::Error>> {
+		let old = Pass::read(File::open(&self.old)?, VerifyMode::No)?;
+		let new = Pass::read(File::open(&self.new)?, VerifyMode::No)?;
+
+		let changes = diff(&old, &new)?;
+		if changes.is_empty() {
+			println!("Passes are identical");
+		}
+		for change in changes {
+			println!("{change}");
+		}
+
+		Ok(())
+	}
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum Change {
+	Added {
+		key: String,
+		new: String,
+	},
+	Removed {
+		key: String,
+		old: String,
+	},
+	Changed {
+		key: String,
+		old: String,
+		new: String,
+	},
+}
+
+impl fmt::Display for Change {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			Self::Added { key, new } => write!(f, "{} {key}: {new}", "+".green()),
+			Self::Removed { key, old } => write!(f, "{} {key}: {old}", "-".red()),
+			Self::Changed { key, old, new } => {
+				write!(f, "{} {key}: {} → {}", "~".yellow(), old.red(), new.green())
+			}
+		}
+	}
+}
+
+/// Metadata changes keyed by their json path, then asset changes keyed by their path in the archive
+fn diff(old: &Pass, new: &Pass) -> Result<Vec<Change>, serde_json::Error> {
+	let leaves = |pass: &Pass| -> Result<BTreeMap<String, String>, serde_json::Error> {
+		let mut leaves = BTreeMap::new();
+		flatten(
+			String::new(),
+			serde_json::to_value(&pass.metadata)?,
+			&mut leaves,
+		);
+		Ok(leaves)
+	};
+
+	let mut changes = compare(&leaves(old)?, &leaves(new)?, "");
+	changes.extend(compare(
+		&old.assets.digests(),
+		&new.assets.digests(),
+		"assets/",
+	));
+	Ok(changes)
+}
+
+fn flatten(key: String, value: Value, leaves: &mut BTreeMap<String, String>) {
+	let join = |child: &str| {
+		if key.is_empty() {
+			child.to_owned()
+		} else {
+			format!("{key}.{child}")
+		}
+	};
+
+	match value {
+		// kept as a leaf, or a style or section emptied to `{}` would go unnoticed
+		Value::Object(map) if map.is_empty() => {
+			leaves.insert(key, "{}".into());
+		}
+		Value::Array(values) if values.is_empty() => {
+			leaves.insert(key, "[]".into());
+		}
+		Value::Object(map) => {
+			for (child, value) in map {
+				flatten(join(&child), value, leaves);
+			}
+		}
+		Value::Array(values) => {
+			for (i, value) in values.into_iter().enumerate() {
+				flatten(format!("{key}[{i}]"), value, leaves);
+			}
+		}
+		value => {
+			leaves.insert(key, value.to_string());
+		}
+	}
+}
+
+fn compare(
+	old: &BTreeMap<String, String>,
+	new: &BTreeMap<String, String>,
+	prefix: &str,
+) -> Vec<Change> {
+	let keys = old.keys().chain(new.keys()).collect::<BTreeSet<_>>();
+
+	keys.into_iter()
+		.filter_map(|key| {
+			let key_name = format!("{prefix}{key}");
+			match (old.get(key), new.get(key)) {
+				(None, Some(new)) => Some(Change::Added {
+					key: key_name,
+					new: new.clone(),
+				}),
+				(Some(old), None) => Some(Change::Removed {
+					key: key_name,
+					old: old.clone(),
+				}),
+				(Some(old), Some(new)) if old != new => Some(Change::Changed {
+					key: key_name,
+					old: old.clone(),
+					new: new.clone(),
+				}),
+				_ => None,
+			}
+		})
+		.collect()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::testing;
+	use pkpass::{
+		models::{Field, Fields, PassKind},
+		PassConfig,
+	};
+
+	#[test]
+	fn diff_passes() -> Result<(), Box<dyn std::error::Error>> {
+		let mut old = Pass::new(testing::config());
+		old.metadata.voided = Some(false);
+		old.assets.images.icon.size_x1 = Some(b"icon".to_vec());
+		old.assets.images.strip.size_x1 = Some(b"strip".to_vec());
+
+		let mut new = old.clone();
+		new.metadata.organization_name = "Acme Corp.".into();
+		new.metadata.voided = None;
+		new.metadata.logo_text = Some("Acme".into());
+		new.assets.images.icon.size_x1 = Some(b"new icon".to_vec());
+		new.assets.images.strip.size_x1 = None;
+
+		assert!(diff(&old, &old)?.is_empty());
+
+		let changes = diff(&old, &new)?;
+		assert_eq!(changes.len(), 5, "{changes:?}");
+		assert_eq!(
+			changes[0],
+			Change::Added {
+				key: "logoText".into(),
+				new: r#""Acme""#.into()
+			}
+		);
+		assert_eq!(
+			changes[1],
+			Change::Changed {
+				key: "organizationName".into(),
+				old: r#""Acme Inc.""#.into(),
+				new: r#""Acme Corp.""#.into()
+			}
+		);
+		assert!(matches!(&changes[2], Change::Removed { key, .. } if key == "voided"));
+		assert!(matches!(&changes[3], Change::Changed { key, .. } if key == "assets/icon.png"));
+		assert!(matches!(&changes[4], Change::Removed { key, .. } if key == "assets/strip.png"));
+
+		Ok(())
+	}
+
+	#[test]
+	fn diff_empty_sections() -> Result<(), Box<dyn std::error::Error>> {
+		let generic = Pass::new(testing::config());
+		let ticket = Pass::new(PassConfig {
+			kind: PassKind::EventTicket(Fields::default()),
+			..testing::config()
+		});
+
+		assert_eq!(
+			diff(&generic, &ticket)?,
+			[
+				Change::Added {
+					key: "eventTicket".into(),
+					new: "{}".into()
+				},
+				Change::Removed {
+					key: "generic".into(),
+					old: "{}".into()
+				},
+			]
+		);
+
+		let mut filled = generic.clone();
+		filled
+			.metadata
+			.kind_fields_mut()
+			.expect("a style is set")
+			.primary
+			.push(Field::new("name", "Jane"));
+		let changes = diff(&filled, &generic)?;
+		assert!(
+			changes.contains(&Change::Added {
+				key: "generic".into(),
+				new: "{}".into()
+			}),
+			"{changes:?}"
+		);
+
+		Ok(())
+	}
+}
@@ -1,14 +1,25 @@
 use clap::Parser;
-use pkpass::{sign::VerifyMode, template::Template, Pass};
+use pkpass::{
+	sign::{Identity, VerifyMode},
+	template::Template,
+	Pass,
+};
 use ron::ser::PrettyConfig;
 use std::{
 	fs::{self, File},
-	path::PathBuf,
+	io::Write,
+	path::{Path, PathBuf},
 	str::FromStr,
 };
 
 mod create;
 mod crypto;
+mod diff;
+#[cfg(feature = "net")]
+mod net;
+mod sign;
+#[cfg(test)]
+mod testing;
 
 #[derive(clap::Parser)]
 #[command(version, about, long_about = None)]
@@ -36,6 +47,12 @@ enum Command {
 	/// Create a working pass from plain arguments
 	Render(RenderArgs),
 
+	/// Sign an existing pass, replacing its previous signature if any
+	Sign(sign::Args),
+
+	/// Print the metadata and assets that changed between two passes
+	Diff(diff::Args),
+
 	#[clap(subcommand)]
 	Crypto(crypto::Command),
 }
@@ -47,6 +64,8 @@ impl Exec for Command {
 			Self::Convert(args) => args.run(),
 			Self::Create(args) => args.run(),
 			Self::Render(args) => args.run(),
+			Self::Sign(args) => args.run(),
+			Self::Diff(args) => args.run(),
 			Self::Crypto(args) => args.run(),
 		}
 	}
@@ -57,20 +76,68 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
 	args.command.run()
 }
 
+/// Write a pass to the `output` file, or stream it to `stdout` when `output` is `-`
+fn write_pass(
+	pass: &Pass,
+	identity: &Identity,
+	output: &Path,
+	stdout: impl Write,
+) -> Result<(), Box<dyn std::error::Error>> {
+	if output == Path::new("-") {
+		pass.write_to(identity, stdout)?;
+	} else {
+		let file = fs::OpenOptions::new()
+			.write(true)
+			.create(true)
+			.truncate(true)
+			.open(output)?;
+		pass.write(identity, file)?;
+		println!("Wrote pass to `{}`", output.display());
+	}
+
+	Ok(())
+}
+
 #[derive(clap::Args)]
 struct ReadArgs {
+	/// Pass file, or http(s) url with the `net` feature
 	file: PathBuf,
 
 	#[clap(long, value_parser = VerifyMode::from_str, default_value_t)]
 	verify_mode: VerifyMode,
+
+	/// Header sent when downloading the pass (e.g. `Authorization: ApplePass token`)
+	#[cfg(feature = "net")]
+	#[clap(long = "header", value_name = "NAME:VALUE")]
+	headers: Vec<String>,
 }
 
 impl Exec for ReadArgs {
 	fn run(self) -> Result<(), Box<dyn std::error::Error>> {
-		let pkpass = Pass::read(File::open(self.file)?, self.verify_mode)?;
+		let url = self
+			.file
+			.to_str()
+			.filter(|file| file.starts_with("http://") || file.starts_with("https://"));
+
+		let pkpass = match url {
+			#[cfg(feature = "net")]
+			Some(url) => {
+				let pass = net::download(url, &self.headers)?;
+				Pass::read(std::io::Cursor::new(pass), self.verify_mode)?
+			}
+			#[cfg(not(feature = "net"))]
+			Some(_) => return Err("reading a pass from a url requires the `net` feature".into()),
+			None => Pass::read(File::open(&self.file)?, self.verify_mode)?,
+		};
 
+		let size = pkpass.size_breakdown();
 		dbg!(pkpass.metadata);
 
+		println!("Total size: {} bytes", size.total);
+		for (path, bytes) in size.largest(5) {
+			println!("  {bytes:>9} {path}");
+		}
+
 		// TODO: need custom print, else prints pictures' bytes
 		// dbg!(pkpass.assets)
 
@@ -87,10 +154,15 @@ struct ConvertArgs {
 
 impl Exec for ConvertArgs {
 	fn run(self) -> Result<(), Box<dyn std::error::Error>> {
-		let output = self
-			.output
-			.unwrap_or_else(|| self.pass.file_name().unwrap().into())
-			.with_extension("ron");
+		let output = match self.output {
+			Some(output) => output,
+			None => self
+				.pass
+				.file_name()
+				.ok_or("pass path has no file name")?
+				.into(),
+		}
+		.with_extension("ron");
 		let output = fs::OpenOptions::new()
 			.write(true)
 			.create(true)
@@ -130,3 +202,48 @@ impl Exec for RenderArgs {
 		Ok(())
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use pkpass::{
+		models::{Fields, PassKind},
+		PassConfig,
+	};
+	use std::{env, process};
+
+	#[test]
+	fn convert_keeps_grouping_identifier() -> Result<(), Box<dyn std::error::Error>> {
+		let dir = env::temp_dir().join(format!("pkp-convert-{}", process::id()));
+		fs::create_dir_all(&dir)?;
+
+		let mut pass = Pass::new(PassConfig {
+			organization_name: "Example Airways".into(),
+			description: "Boarding pass for flight EX123".into(),
+			serial_number: "EX123-12C".into(),
+			kind: PassKind::BoardingPass(Fields::default()),
+		});
+		pass.metadata.grouping_identifier = Some("trip-42".into());
+		pass.write(&testing::identity(), File::create(dir.join("leg.pkpass"))?)?;
+
+		let result = ConvertArgs {
+			pass: dir.join("leg.pkpass"),
+			output: Some(dir.join("leg.ron")),
+		}
+		.run()
+		.and_then(|()| {
+			Ok(ron::de::from_reader::<_, Template>(File::open(
+				dir.join("leg.ron"),
+			)?)?)
+		});
+		fs::remove_dir_all(&dir)?;
+
+		let rendered = result?.render();
+		assert_eq!(
+			rendered.metadata.grouping_identifier.as_deref(),
+			Some("trip-42")
+		);
+
+		Ok(())
+	}
+}
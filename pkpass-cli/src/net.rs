@@ -0,0 +1,39 @@
+use reqwest::blocking::Client;
+
+/// Download a pass, sending each `Name: value` header along
+pub(crate) fn download(
+	url: &str,
+	headers: &[String],
+) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+	let mut request = Client::new().get(url);
+	for header in headers {
+		let (name, value) = parse_header(header)?;
+		request = request.header(name, value);
+	}
+
+	let response = request.send()?.error_for_status()?;
+	Ok(response.bytes()?.to_vec())
+}
+
+fn parse_header(header: &str) -> Result<(&str, &str), String> {
+	header
+		.split_once(':')
+		.map(|(name, value)| (name.trim(), value.trim()))
+		.filter(|(name, _)| !name.is_empty())
+		.ok_or_else(|| format!("header `{header}` is not formatted as `Name: value`"))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn parse_headers() {
+		assert_eq!(
+			parse_header("Authorization: ApplePass abc:def"),
+			Ok(("Authorization", "ApplePass abc:def"))
+		);
+		assert!(parse_header("Authorization").is_err());
+		assert!(parse_header(": value").is_err());
+	}
+}
@@ -0,0 +1,155 @@
+use crate::Exec;
+use pkpass::{
+	sign::{Identity, SigningPen, VerifyMode},
+	Pass, ReadOptions,
+};
+use std::{
+	fs::{self, File},
+	io::{self, Write},
+	path::PathBuf,
+};
+
+#[derive(clap::Args)]
+pub(crate) struct Args {
+	/// Pass to sign, signed or not (e.g. unsigned.pkpass)
+	input: PathBuf,
+
+	/// Signed pass destination file (e.g. signed.pkpass), `-` for stdout
+	output: PathBuf,
+
+	/// PKCS#12 archive DER-encoded used to sign the pass (e.g. pkpass.p12)
+	#[clap(long, env = "PKPASS_IDENTITY")]
+	identity: PathBuf,
+
+	/// Password of the PKCS#12 archive
+	#[clap(long, env = "PKPASS_IDENTITY_PASSWORD", default_value = "")]
+	password: String,
+}
+
+impl Exec for Args {
+	fn run(self) -> Result<(), Box<dyn std::error::Error>> {
+		self.run_with(io::stdout().lock())
+	}
+}
+
+impl Args {
+	fn run_with(self, stdout: impl Write) -> Result<(), Box<dyn std::error::Error>> {
+		let archive = fs::read(&self.identity)?;
+		let pen = SigningPen::from_pkcs12_der(&archive, &self.password)?;
+		let identity = Identity::from_apple_pen(pen)?;
+
+		// re-signing replaces the signature, and must not drop unknown files
+		let options = ReadOptions::default()
+			.verify(VerifyMode::No)
+			.keep_unknown_assets(true);
+		let mut pass = Pass::read_with(File::open(&self.input)?, options)?;
+		pass.strip_signature();
+
+		crate::write_pass(&pass, &identity, &self.output, stdout)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::testing;
+	use openssl::x509::{X509Ref, X509};
+	use std::{env, io::Cursor, process};
+
+	/// Write an unsigned pass and a signing identity in a fresh directory
+	fn fixtures(name: &str) -> Result<(PathBuf, X509), Box<dyn std::error::Error>> {
+		let dir = env::temp_dir().join(format!("pkp-{name}-{}", process::id()));
+		fs::create_dir_all(&dir)?;
+
+		let (p12, cert) = SigningPen::test_pkcs12_der()?;
+		fs::write(dir.join("pkpass.p12"), p12)?;
+
+		let pass = Pass::new(testing::config());
+		pass.write(
+			&testing::identity(),
+			File::create(dir.join("unsigned.pkpass"))?,
+		)?;
+
+		Ok((dir, cert))
+	}
+
+	#[test]
+	fn sign_unsigned_pass() -> Result<(), Box<dyn std::error::Error>> {
+		let (dir, cert) = fixtures("sign")?;
+
+		let result = Args {
+			input: dir.join("unsigned.pkpass"),
+			output: dir.join("signed.pkpass"),
+			identity: dir.join("pkpass.p12"),
+			password: String::new(),
+		}
+		.run()
+		.and_then(|()| {
+			Ok(Pass::read(
+				File::open(dir.join("signed.pkpass"))?,
+				VerifyMode::No,
+			)?)
+		});
+		fs::remove_dir_all(&dir)?;
+
+		assert_eq!(result?.signer().map(X509Ref::to_owned), Some(cert));
+
+		Ok(())
+	}
+
+	#[test]
+	fn sign_to_stdout() -> Result<(), Box<dyn std::error::Error>> {
+		let (dir, cert) = fixtures("sign-stdout")?;
+
+		let mut stdout = vec![];
+		let result = Args {
+			input: dir.join("unsigned.pkpass"),
+			output: "-".into(),
+			identity: dir.join("pkpass.p12"),
+			password: String::new(),
+		}
+		.run_with(&mut stdout);
+		fs::remove_dir_all(&dir)?;
+		result?;
+
+		assert!(stdout.starts_with(b"PK\x03\x04"));
+		let signed = Pass::read(Cursor::new(stdout), VerifyMode::No)?;
+		assert_eq!(signed.signer().map(X509Ref::to_owned), Some(cert));
+
+		Ok(())
+	}
+
+	#[test]
+	fn sign_keeps_unknown_assets() -> Result<(), Box<dyn std::error::Error>> {
+		let (dir, _) = fixtures("sign-unknown")?;
+
+		let mut pass = Pass::new(testing::config());
+		pass.insert_raw_asset("audio.caf".into(), b"audio".to_vec())?;
+		pass.write(
+			&testing::identity(),
+			File::create(dir.join("unsigned.pkpass"))?,
+		)?;
+
+		let mut stdout = vec![];
+		let result = Args {
+			input: dir.join("unsigned.pkpass"),
+			output: "-".into(),
+			identity: dir.join("pkpass.p12"),
+			password: String::new(),
+		}
+		.run_with(&mut stdout);
+		fs::remove_dir_all(&dir)?;
+		result?;
+
+		let options = ReadOptions::default()
+			.verify(VerifyMode::No)
+			.keep_unknown_assets(true);
+		let signed = Pass::read_with(Cursor::new(stdout), options)?;
+		assert_eq!(
+			signed.assets.raw().get("audio.caf").map(Vec::as_slice),
+			Some(&b"audio"[..])
+		);
+
+		Ok(())
+	}
+}
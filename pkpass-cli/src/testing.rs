@@ -0,0 +1,22 @@
+//! Fixtures shared by tests
+
+use pkpass::{
+	models::{Fields, PassKind},
+	sign::Identity,
+	PassConfig,
+};
+
+/// Configuration of a generic pass, set `kind` for other styles
+pub fn config() -> PassConfig {
+	PassConfig {
+		organization_name: "Acme Inc.".into(),
+		description: "A test pass".into(),
+		serial_number: "1234".into(),
+		kind: PassKind::Generic(Fields::default()),
+	}
+}
+
+/// Identity writing unsigned passes
+pub fn identity() -> Identity {
+	Identity::new_no_signature("pass.com.acme".into(), "ACME123456".into())
+}
@@ -0,0 +1,47 @@
+use openssl::pkcs12::Pkcs12;
+use pkpass::{
+	models::{Fields, PassKind},
+	sign::{Identity, SigningPen, VerifyMode},
+	Pass, PassConfig,
+};
+use std::{fs, io};
+
+const ICON: &[u8; 314_069] = include_bytes!("assets/icon.png");
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+	let identity = get_identity()?;
+
+	let mut pass = Pass::new(PassConfig {
+		organization_name: "Acme Inc.".into(),
+		description: "Acme loyalty card".into(),
+		serial_number: "CARD-0042".into(),
+		kind: PassKind::StoreCard(Fields::default()),
+	});
+	pass.assets.images.icon.size_x1.replace(ICON.to_vec());
+	pass.metadata
+		.set_store_card_balance("25.00", "USD", Some("balance"))?;
+
+	pass.write(&identity, fs::File::create("store-card.pkpass")?)?;
+
+	// later, after a purchase: update the balance and issue the pass again
+	let mut pass = Pass::read(fs::File::open("store-card.pkpass")?, VerifyMode::Yes)?;
+	pass.metadata
+		.set_store_card_balance("17.40", "USD", Some("balance"))?;
+
+	pass.resign(&identity, fs::File::create("store-card.pkpass")?)?;
+
+	Ok(())
+}
+
+fn get_identity() -> io::Result<Identity> {
+	let archive = fs::read("certs/pkpass.p12")?;
+	let pkcs12_err = "Signing identity is not a valid PKCS#12 archive DER-encoded";
+	let pkcs12 = Pkcs12::from_der(&archive)
+		.map_err(|_err| io::Error::new(io::ErrorKind::InvalidData, pkcs12_err))?
+		.parse2("")?;
+
+	let pen = SigningPen::from_pkcs12(pkcs12)?;
+	let identity = Identity::from_apple_pen(pen)?;
+
+	Ok(identity)
+}
@@ -0,0 +1,174 @@
+//! Several passes delivered at once in a `.pkpasses` archive
+//!
+//! A bundle is a zip of signed `pkpass` files, such as the boarding passes
+//! of connecting flights. Wallet offers to add all of them in one go.
+
+use crate::{
+	sign::{Identity, VerifyMode},
+	Pass, ReadOptions, Result,
+};
+use std::io::{Cursor, Read, Seek, Write};
+use zip::{write::SimpleFileOptions, ZipArchive};
+
+/// Passes read from or written to a `pkpasses` file, such as a family of tickets
+#[derive(Debug, Clone, Default)]
+pub struct PassBundle {
+	pub passes: Vec<Pass>,
+}
+
+impl PassBundle {
+	#[must_use]
+	pub const fn new(passes: Vec<Pass>) -> Self {
+		Self { passes }
+	}
+
+	/// Read a `pkpasses` file, see [`Pass::read_bundle`].
+	///
+	/// # Errors
+	///
+	/// Fails if the outer archive is malformed or if any pass could not be read.
+	pub fn read(reader: impl Read + Seek, verify: VerifyMode) -> Result<Self> {
+		Pass::read_bundle(reader, verify).map(Self::new)
+	}
+
+	/// Bundle every pass to a `pkpasses` file, all signed with `identity`.
+	///
+	/// # Errors
+	///
+	/// Fails if any pass could not be written or if the outer archive could not be written.
+	pub fn write(&self, identity: &Identity, writer: impl Write + Seek) -> Result<()> {
+		write_bundle(self.passes.iter().map(|pass| (pass, identity)), writer)
+	}
+}
+
+impl From<Vec<Pass>> for PassBundle {
+	fn from(passes: Vec<Pass>) -> Self {
+		Self::new(passes)
+	}
+}
+
+impl Pass {
+	/// Read every pass of a `pkpasses` file, in archive order, verifying their signature according to `verify`.
+	///
+	/// Entries not ending with `.pkpass`, such as directories, are skipped.
+	///
+	/// # Errors
+	///
+	/// Fails if the outer archive is malformed or if any pass could not be read, see [`Pass::read`].
+	pub fn read_bundle(reader: impl Read + Seek, verify: VerifyMode) -> Result<Vec<Self>> {
+		let options = ReadOptions::default().verify(verify);
+		let mut zip = ZipArchive::new(reader)?;
+
+		let mut passes = vec![];
+		for index in 0..zip.len() {
+			let mut file = zip.by_index(index)?;
+			if !file.is_file() || !file.name().ends_with(".pkpass") {
+				continue;
+			}
+
+			let mut pkpass = vec![];
+			file.read_to_end(&mut pkpass)?;
+			passes.push(Self::read_with(Cursor::new(pkpass), options.clone())?);
+		}
+
+		Ok(passes)
+	}
+
+	/// Bundle passes to a `pkpasses` file, each signed with its own identity.
+	///
+	/// Passes are named `pass1.pkpass`, `pass2.pkpass` and so on, in order.
+	///
+	/// # Errors
+	///
+	/// Fails if any pass could not be written, see [`Pass::write`], or if the
+	/// outer archive could not be written.
+	pub fn write_bundle(passes: &[(Self, Identity)], writer: impl Write + Seek) -> Result<()> {
+		write_bundle(
+			passes.iter().map(|(pass, identity)| (pass, identity)),
+			writer,
+		)
+	}
+}
+
+fn write_bundle<'a>(
+	passes: impl IntoIterator<Item = (&'a Pass, &'a Identity)>,
+	writer: impl Write + Seek,
+) -> Result<()> {
+	let mut zip = zip::ZipWriter::new(writer);
+	// passes are zip archives already, compressing them again gains nothing
+	let options = SimpleFileOptions::default().compression_method(zip::CompressionMethod::Stored);
+
+	for (index, (pass, identity)) in passes.into_iter().enumerate() {
+		let mut pkpass = Cursor::new(vec![]);
+		pass.write(identity, &mut pkpass)?;
+
+		zip.start_file(format!("pass{}.pkpass", index + 1), options)?;
+		zip.write_all(pkpass.get_ref())?;
+	}
+
+	zip.finish()?;
+
+	Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{
+		models::{Fields, PassKind},
+		testing::{config, identity},
+		PassConfig,
+	};
+
+	fn pass(serial_number: &str) -> Pass {
+		Pass::new(PassConfig {
+			serial_number: serial_number.into(),
+			kind: PassKind::BoardingPass(Fields::default()),
+			..config()
+		})
+	}
+
+	#[test]
+	fn bundle_round_trip() -> Result<()> {
+		let mut pkpasses = Cursor::new(vec![]);
+		Pass::write_bundle(
+			&[(pass("EX123"), identity()), (pass("EX456"), identity())],
+			&mut pkpasses,
+		)?;
+
+		let zip = ZipArchive::new(Cursor::new(pkpasses.get_ref()))?;
+		assert_eq!(
+			zip.file_names().collect::<Vec<_>>(),
+			["pass1.pkpass", "pass2.pkpass"]
+		);
+
+		pkpasses.set_position(0);
+		let passes = Pass::read_bundle(pkpasses, VerifyMode::No)?;
+		let serial_numbers = passes
+			.iter()
+			.map(|pass| pass.metadata.serial_number())
+			.collect::<Vec<_>>();
+		assert_eq!(serial_numbers, ["EX123", "EX456"]);
+
+		Ok(())
+	}
+
+	#[test]
+	fn pass_bundle_round_trip() -> Result<()> {
+		let identity = identity();
+		let bundle = PassBundle::from(vec![pass("TICKET-1"), pass("TICKET-2")]);
+
+		let mut pkpasses = Cursor::new(vec![]);
+		bundle.write(&identity, &mut pkpasses)?;
+
+		pkpasses.set_position(0);
+		let read = PassBundle::read(pkpasses, VerifyMode::No)?;
+		assert_eq!(read.passes.len(), 2);
+		for (read, written) in read.passes.iter().zip(&bundle.passes) {
+			assert_eq!(read.content_hash()?, written.content_hash()?);
+			assert_eq!(read.metadata.team_identifier(), "ACME123456");
+		}
+
+		Ok(())
+	}
+}
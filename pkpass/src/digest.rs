@@ -0,0 +1,85 @@
+//! Digests of manifests and content hashes, computed by the pure-Rust `sha1`
+//! and `sha2` crates with the `rustcrypto` feature and by openssl otherwise
+
+#[cfg(not(any(feature = "sign", feature = "rustcrypto")))]
+compile_error!(
+	"hashing needs openssl from the `sign` feature or RustCrypto from the `rustcrypto` feature"
+);
+
+#[cfg(not(feature = "rustcrypto"))]
+pub(crate) use openssl::sha::{sha1, sha256, Sha256};
+
+#[cfg(feature = "rustcrypto")]
+pub(crate) fn sha1(data: &[u8]) -> [u8; 20] {
+	use ::sha1::Digest;
+
+	::sha1::Sha1::digest(data).into()
+}
+
+#[cfg(feature = "rustcrypto")]
+pub(crate) fn sha256(data: &[u8]) -> [u8; 32] {
+	use sha2::Digest;
+
+	sha2::Sha256::digest(data).into()
+}
+
+/// Incremental SHA-256, with the same interface as openssl's
+#[cfg(feature = "rustcrypto")]
+pub(crate) struct Sha256(sha2::Sha256);
+
+#[cfg(feature = "rustcrypto")]
+impl Sha256 {
+	pub(crate) fn new() -> Self {
+		use sha2::Digest;
+
+		Self(sha2::Sha256::new())
+	}
+
+	pub(crate) fn update(&mut self, data: &[u8]) {
+		use sha2::Digest;
+
+		self.0.update(data);
+	}
+
+	pub(crate) fn finish(self) -> [u8; 32] {
+		use sha2::Digest;
+
+		self.0.finalize().into()
+	}
+}
+
+/// Compare in constant time, so checking a digest doesn't leak how much of it matched
+pub(crate) fn eq(a: &[u8], b: &[u8]) -> bool {
+	use subtle::ConstantTimeEq;
+
+	a.ct_eq(b).into()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn known_digests() {
+		assert_eq!(
+			hex::encode(sha1(b"abc")),
+			"a9993e364706816aba3e25717850c26c9cd0d89d"
+		);
+		assert_eq!(
+			hex::encode(sha256(b"abc")),
+			"ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+		);
+
+		let mut hasher = Sha256::new();
+		hasher.update(b"a");
+		hasher.update(b"bc");
+		assert_eq!(hasher.finish(), sha256(b"abc"));
+	}
+
+	#[test]
+	fn constant_time_eq() {
+		assert!(eq(b"a9993e36", b"a9993e36"));
+		assert!(!eq(b"a9993e36", b"a9993e37"));
+		assert!(!eq(b"a9993e36", b"a9993e"));
+	}
+}
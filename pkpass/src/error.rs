@@ -3,6 +3,102 @@ pub enum Error {
 	#[error("ManifestSignatureMismatch: `{0}` calculated signature didn't match the one in the manifest")]
 	ManifestSignatureMismatch(String),
 
+	#[error("IdentityMismatch: `{field}` is `{pass}` in the pass but `{certificate}` in the signer certificate")]
+	IdentityMismatch {
+		field: &'static str,
+		pass: String,
+		certificate: String,
+	},
+
+	#[error(
+		"KeyCertMismatch: the private key doesn't match the public key of the signer certificate"
+	)]
+	KeyCertMismatch,
+
+	#[error("SignerNotPinned: the pass is not signed by a certificate with a pinned fingerprint")]
+	SignerNotPinned,
+
+	#[error(
+		"UnknownArchiveFormat: input is neither a zip archive, nor one wrapped in gzip or base64"
+	)]
+	UnknownArchiveFormat,
+
+	#[error("MissingFile: archive has no `{0}`")]
+	MissingFile(&'static str),
+
+	#[error("ReservedAssetPath: `{0}` is a file of the archive itself, it cannot be an asset")]
+	ReservedAssetPath(String),
+
+	#[error("UnsafeAssetPath: `{0}` is absolute or escapes the archive with `..`")]
+	UnsafeAssetPath(String),
+
+	#[error("PassTooLarge: the archive exceeds the budget of {0} bytes")]
+	PassTooLarge(u64),
+
+	#[error("UnknownPassKey: `pass.json` has the key `{0}` this crate doesn't know, it may come from a newer format")]
+	UnknownPassKey(String),
+
+	#[error("UnsupportedFormatVersion: pass format version {0} is not supported, expected 1")]
+	UnsupportedFormatVersion(u64),
+
+	#[error("MultiplePassKinds: a pass has exactly one style but {0:?} are set")]
+	MultiplePassKinds(Vec<&'static str>),
+
+	#[error("MissingPassKind: a pass needs one of boardingPass, coupon, eventTicket, generic or storeCard")]
+	MissingPassKind,
+
+	#[error("InvalidColor: `{color}` is not a valid color, {reason}")]
+	InvalidColor { color: String, reason: String },
+
+	#[error("InvalidLanguageTag: `{0}` is not a valid language tag, such as `fr` or `zh-Hans`")]
+	InvalidLanguageTag(String),
+
+	#[error("InvalidIataCode: `{0}` is not a valid IATA code")]
+	InvalidIataCode(String),
+
+	#[error("InvalidCurrencyCode: `{0}` is not an ISO 4217 currency code, such as `EUR` or `USD`")]
+	InvalidCurrencyCode(String),
+
+	#[error("InvalidCoordinate: {axis} {value} is out of range -{max} to {max}")]
+	InvalidCoordinate {
+		axis: &'static str,
+		value: f64,
+		max: f64,
+	},
+
+	#[error(
+		"TooManyLocations: a pass has at most {} relevant locations",
+		crate::models::Metadata::MAX_LOCATIONS
+	)]
+	TooManyLocations,
+
+	#[error("MissingSemanticTag: `{0}` is required")]
+	MissingSemanticTag(&'static str),
+
+	#[error("SemanticTagNotApplicable: `{key}` is not meant for a {pass}")]
+	SemanticTagNotApplicable {
+		key: &'static str,
+		pass: &'static str,
+	},
+
+	#[error("UnsupportedFeature: {what} requires the `{feature}` feature")]
+	UnsupportedFeature {
+		what: &'static str,
+		feature: &'static str,
+	},
+
+	#[error(
+		"InvalidVerifyMode: `{0}` is not a verify mode, expected one of: {}",
+		crate::sign::VerifyMode::accepted()
+	)]
+	InvalidVerifyMode(String),
+
+	#[error("NfcMessageTooLong: NFC message is {0} bytes long, Wallet truncates it past {max} bytes", max = crate::models::Nfc::MAX_MESSAGE_LEN)]
+	NfcMessageTooLong(usize),
+
+	#[error("UnboundVariable: template uses `{{{{{0}}}}}` but no value is bound to it")]
+	UnboundVariable(String),
+
 	// ---
 	#[error("Zip: {0}")]
 	Zip(#[from] zip::result::ZipError),
@@ -10,11 +106,16 @@ pub enum Error {
 	#[error("Io: {0}")]
 	Io(#[from] std::io::Error),
 
+	#[cfg(feature = "sign")]
 	#[error("OpenSsl: {0}")]
 	OpenSsl(#[from] openssl::error::ErrorStack),
 
 	#[error("Json: {0}")]
 	Json(#[from] serde_json::error::Error),
+
+	#[cfg(feature = "image")]
+	#[error("Image: {0}")]
+	Image(#[from] image::ImageError),
 }
 
 pub type Result<T> = std::result::Result<T, Error>;
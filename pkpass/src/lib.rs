@@ -1,31 +1,75 @@
 //! pkpass
+//!
+//! # Thread safety
+//!
+//! Public types are `Send + Sync`, including [`sign::Identity`] which holds
+//! the signing key. Writing a pass only borrows the identity, so a server can
+//! load it once and share it across threads behind an `Arc`.
 
+#[cfg(feature = "apple")]
+use crate::sign::certificates;
 use crate::{
-	models::{Assets, Manifest, Metadata, PassKind},
-	sign::{certificates, Identity, VerifyMode},
+	digest::{sha256, Sha256},
+	lint::Deprecation,
+	models::{Assets, Manifest, ManifestHashPolicy, Metadata, PassKind, RgbColor},
+	sign::{Identity, VerifyMode},
 };
+use base64::{engine::general_purpose::STANDARD, Engine};
+use chrono::{DateTime, SubsecRound, Utc};
+#[cfg(feature = "apple")]
+use openssl::x509::X509PurposeId;
+#[cfg(feature = "sign")]
 use openssl::{
 	pkcs7::{Pkcs7, Pkcs7Flags},
 	stack::Stack,
-	x509::{store::X509StoreBuilder, X509PurposeId},
+	x509::{store::X509StoreBuilder, X509Ref, X509},
 };
+#[cfg(feature = "sign")]
+use std::sync::Arc;
 use std::{
-	io::{Read, Seek, Write},
-	mem,
+	borrow::Cow,
+	fs,
+	io::{self, Cursor, Read, Seek, Write},
+	path::Path,
 };
 use zip::{result::ZipError, write::SimpleFileOptions, ZipArchive};
 
+/// Emit a `tracing` event when the feature is enabled, compiled out otherwise
+macro_rules! trace_event {
+	($level:ident, $($arg:tt)+) => {
+		#[cfg(feature = "tracing")]
+		tracing::$level!($($arg)+);
+	};
+}
+
+mod digest;
 mod error;
 
+pub mod bundle;
+pub mod lint;
 pub mod models;
 pub mod sign;
+pub mod size;
+pub mod source;
 pub mod template;
+#[cfg(any(test, feature = "test-util"))]
+mod testing;
 pub use error::{Error, Result};
 
+/// Content type to serve a `pkpass` file with, Safari refuses to add passes served otherwise
+pub const MIME_PKPASS: &str = "application/vnd.apple.pkpass";
+
+/// Content type to serve a `pkpasses` bundle of several passes with, see [`bundle`]
+pub const MIME_PKPASSES: &str = "application/vnd.apple.pkpasses";
+
 #[derive(Debug, Clone)]
 pub struct Pass {
 	pub metadata: Metadata,
 	pub assets: Assets,
+
+	#[cfg(feature = "sign")]
+	signer: Option<X509>,
+	deprecations: Vec<Deprecation>,
 }
 
 #[derive(Debug)]
@@ -36,6 +80,93 @@ pub struct PassConfig {
 	pub kind: PassKind,
 }
 
+/// Tune how [`Pass::read_with`] reads and checks an archive
+///
+/// # Trust model
+///
+/// The signature only covers `manifest.json`, which lists a digest for every
+/// other file of the archive. A file is authenticated by the signature only
+/// once its content is checked against that digest, so both checks are
+/// needed to trust the content of a pass.
+#[derive(Debug, Clone)]
+pub struct ReadOptions {
+	verify: VerifyMode,
+	verify_manifest_digests: bool,
+	keep_unknown_assets: bool,
+}
+
+impl Default for ReadOptions {
+	fn default() -> Self {
+		Self {
+			verify: VerifyMode::default(),
+			verify_manifest_digests: true,
+			keep_unknown_assets: false,
+		}
+	}
+}
+
+impl ReadOptions {
+	/// How to check the signature, defaults to [`VerifyMode::default`]
+	#[must_use]
+	#[cfg_attr(not(feature = "sign"), allow(clippy::missing_const_for_fn))]
+	pub fn verify(mut self, verify: VerifyMode) -> Self {
+		self.verify = verify;
+		self
+	}
+
+	/// Whether to check `pass.json` and each asset against its digest in the
+	/// manifest, defaults to `true`
+	///
+	/// Skipping it saves hashing every file, but the signature then vouches
+	/// for none of them: a verified signature over the manifest says nothing
+	/// about files that were never compared to it, so they can be replaced
+	/// freely. Only skip it for archives trusted by other means, such as ones
+	/// written by this process or fetched over an authenticated channel.
+	#[must_use]
+	pub const fn verify_manifest_digests(mut self, verify_manifest_digests: bool) -> Self {
+		self.verify_manifest_digests = verify_manifest_digests;
+		self
+	}
+
+	/// Whether to keep files that are neither an image nor a localization in
+	/// [`Assets::raw`] rather than failing, defaults to `false`
+	///
+	/// Real-world passes may carry unexpected files, keeping them makes
+	/// reading then writing such a pass lossless.
+	#[must_use]
+	pub const fn keep_unknown_assets(mut self, keep_unknown_assets: bool) -> Self {
+		self.keep_unknown_assets = keep_unknown_assets;
+		self
+	}
+}
+
+/// Tune how [`Pass::write_with`] writes an archive
+#[derive(Debug, Clone, Copy, Default)]
+pub struct WriteOptions {
+	manifest_hash: ManifestHashPolicy,
+	max_size: Option<u64>,
+}
+
+impl WriteOptions {
+	/// Digest of the files in `manifest.json`, defaults to [`ManifestHashPolicy::Sha1`]
+	#[must_use]
+	pub const fn manifest_hash(mut self, manifest_hash: ManifestHashPolicy) -> Self {
+		self.manifest_hash = manifest_hash;
+		self
+	}
+
+	/// Fail with [`Error::PassTooLarge`] rather than write an archive larger
+	/// than `max_size` bytes, such as an upload limit, unlimited by default
+	///
+	/// Writing stops before the budget is exceeded, leaving the writer with a
+	/// truncated archive to discard.
+	#[must_use]
+	pub const fn max_size(mut self, max_size: u64) -> Self {
+		self.max_size = Some(max_size);
+		self
+	}
+}
+
 /// Pass creation
 impl Pass {
 	#[must_use]
@@ -43,95 +174,495 @@ impl Pass {
 		Self {
 			metadata: Metadata::new(config),
 			assets: Assets::default(),
+			#[cfg(feature = "sign")]
+			signer: None,
+			deprecations: Vec::new(),
+		}
+	}
+
+	/// Set the foreground color of the pass
+	///
+	/// # Errors
+	///
+	/// Fails if the color could not be converted, e.g. a malformed `rgb(...)` string.
+	pub fn with_foreground_color<C: TryInto<RgbColor>>(
+		mut self,
+		color: C,
+	) -> std::result::Result<Self, C::Error> {
+		self.metadata.foreground_color = Some(color.try_into()?);
+		Ok(self)
+	}
+
+	/// Set the background color of the pass
+	///
+	/// # Errors
+	///
+	/// Fails if the color could not be converted, e.g. a malformed `rgb(...)` string.
+	pub fn with_background_color<C: TryInto<RgbColor>>(
+		mut self,
+		color: C,
+	) -> std::result::Result<Self, C::Error> {
+		self.metadata.background_color = Some(color.try_into()?);
+		Ok(self)
+	}
+
+	/// Set the label color of the pass
+	///
+	/// # Errors
+	///
+	/// Fails if the color could not be converted, e.g. a malformed `rgb(...)` string.
+	pub fn with_label_color<C: TryInto<RgbColor>>(
+		mut self,
+		color: C,
+	) -> std::result::Result<Self, C::Error> {
+		self.metadata.label_color = Some(color.try_into()?);
+		Ok(self)
+	}
+
+	/// Set when the pass becomes relevant, such as the start time of a movie
+	///
+	/// Fractional seconds are dropped, `pass.json` then holds a W3C timestamp
+	/// with minute and second precision in UTC, such as `2024-07-22T16:30:00Z`.
+	pub fn set_relevant_date(&mut self, date: DateTime<Utc>) {
+		self.metadata.relevant_date = Some(date.trunc_subsecs(0));
+	}
+
+	/// Set when the pass expires, written like [`Pass::set_relevant_date`]
+	pub fn set_expiration_date(&mut self, date: DateTime<Utc>) {
+		self.metadata.expiration_date = Some(date.trunc_subsecs(0));
+	}
+
+	/// Whether to display the strip image without a shine effect, `true` by default
+	///
+	/// The key is omitted from `pass.json` when set to its default.
+	pub fn suppress_strip_shine(&mut self, suppress: bool) {
+		self.metadata.suppress_strip_shine = (!suppress).then_some(false);
+	}
+
+	/// Whether to remove the Share button from the back of the pass, `false` by default
+	///
+	/// The key is omitted from `pass.json` when set to its default. Sharing the
+	/// pass in other ways is still possible, and iOS 10 and earlier ignore it.
+	pub fn sharing_prohibited(&mut self, prohibited: bool) {
+		self.metadata.sharing_prohibited = prohibited.then_some(true);
+	}
+
+	/// Add a file the typed [`Assets`] don't model, such as a vendor file,
+	/// written verbatim to the archive and listed in the manifest
+	///
+	/// Paths of typed assets, such as `icon.png`, replace that asset instead.
+	///
+	/// # Errors
+	///
+	/// Fails with [`Error::ReservedAssetPath`] for `pass.json`, `manifest.json`
+	/// and `signature`, which the archive writes itself, and with
+	/// [`Error::UnsafeAssetPath`] for absolute paths or ones containing `..`.
+	pub fn insert_raw_asset(&mut self, path: String, bytes: Vec<u8>) -> Result<()> {
+		check_asset_path(&path)?;
+		if SPECIAL_FILES
+			.iter()
+			.any(|special| path.eq_ignore_ascii_case(special))
+		{
+			return Err(Error::ReservedAssetPath(path));
 		}
+		self.assets.insert_raw(path, bytes);
+		Ok(())
 	}
 
 	pub(crate) const fn from_raw_parts(metadata: Metadata, assets: Assets) -> Self {
-		Self { metadata, assets }
+		Self {
+			metadata,
+			assets,
+			#[cfg(feature = "sign")]
+			signer: None,
+			deprecations: Vec::new(),
+		}
+	}
+}
+
+/// Signature
+impl Pass {
+	/// Certificate that signed the pass, if it was read from a signed archive
+	#[cfg(feature = "sign")]
+	#[must_use]
+	pub fn signer(&self) -> Option<&X509Ref> {
+		self.signer.as_deref()
+	}
+
+	/// Check the identifiers in `pass.json` are the ones of the signer certificate, as Apple requires.
+	///
+	/// Unsigned passes have nothing to compare against and are accepted.
+	///
+	/// # Errors
+	///
+	/// Fails with [`Error::IdentityMismatch`] if the pass type identifier or the
+	/// team identifier differ from the user id or organizational unit of the signer.
+	#[cfg_attr(not(feature = "sign"), allow(clippy::missing_const_for_fn))]
+	pub fn verify_identity_match(&self) -> Result<()> {
+		#[cfg(feature = "sign")]
+		if let Some(signer) = &self.signer {
+			self.match_identifiers(signer)?;
+		}
+
+		Ok(())
+	}
+
+	#[cfg(feature = "sign")]
+	fn match_identifiers(&self, signer: &X509Ref) -> Result<()> {
+		let (pass_type_id, team_id) = sign::apple_identifiers(signer);
+		for (field, pass, certificate) in [
+			(
+				"passTypeIdentifier",
+				&self.metadata.pass_type_identifier,
+				pass_type_id,
+			),
+			("teamIdentifier", &self.metadata.team_identifier, team_id),
+		] {
+			let certificate = certificate.unwrap_or_default();
+			if *pass != certificate {
+				return Err(Error::IdentityMismatch {
+					field,
+					pass: pass.clone(),
+					certificate,
+				});
+			}
+		}
+
+		Ok(())
+	}
+
+	/// Forget the signature of a pass read from a signed archive
+	///
+	/// Writing always signs with the given [`Identity`], this only ensures
+	/// nothing about the previous signer is carried around.
+	pub fn strip_signature(&mut self) {
+		#[cfg(feature = "sign")]
+		{
+			self.signer = None;
+		}
+	}
+}
+
+/// Comparison
+impl Pass {
+	/// SHA-256 of the pass content, stable across identical passes
+	///
+	/// The hash covers:
+	/// - `pass.json` serialized with sorted keys and no whitespace, with the
+	///   pass type and team identifiers blanked as they are filled when signing,
+	/// - the path and SHA-256 of every asset, sorted by path.
+	///
+	/// The signature, the signer and the archive layout are not included, so a
+	/// pass hashes the same before and after being written and read back.
+	///
+	/// # Errors
+	///
+	/// Fails if the metadata could not be serialized.
+	pub fn content_hash(&self) -> Result<[u8; 32]> {
+		let mut metadata = self.metadata.clone();
+		metadata.pass_type_identifier.clear();
+		metadata.team_identifier.clear();
+		let pass_json = serde_json::to_vec(&metadata.to_sorted_value()?)?;
+
+		let mut hasher = Sha256::new();
+		hasher.update(b"pass.json\0");
+		hasher.update(&sha256(&pass_json));
+		self.assets.hash_into(&mut hasher);
+		Ok(hasher.finish())
 	}
 }
 
 /// Reading and writing
 impl Pass {
+	/// Deprecated keys of `pass.json` found when reading the pass, still honored
+	#[must_use]
+	pub fn deprecations(&self) -> &[Deprecation] {
+		&self.deprecations
+	}
+
+	/// Read a `pkpass` file, verifying its signature according to `verify`.
+	///
+	/// # Errors
+	///
+	/// Fails if the archive is malformed, if an asset is not recognized or
+	/// if the signature or the manifest doesn't match the content.
 	pub fn read(reader: impl Read + Seek, verify: VerifyMode) -> Result<Self> {
+		Self::read_with(reader, ReadOptions::default().verify(verify))
+	}
+
+	/// Read a `pkpass` file even if it was base64 encoded or gzip compressed,
+	/// such as by some APIs or misconfigured servers, verifying its signature
+	/// according to `verify`.
+	///
+	/// Layers are detected from the content and unwrapped until the zip
+	/// archive is found. Use [`Pass::read`] to only accept actual archives.
+	///
+	/// # Errors
+	///
+	/// Fails with [`Error::UnknownArchiveFormat`] if the input is none of
+	/// these, with [`Error::PassTooLarge`] if a gzip layer inflates past
+	/// 64 MiB, or for the same reasons as [`Pass::read`].
+	pub fn read_auto(bytes: &[u8], verify: VerifyMode) -> Result<Self> {
+		// an archive is either stored as is, or wrapped at most twice such as gzip then base64
+		let mut bytes = Cow::Borrowed(bytes);
+		for _ in 0..3 {
+			if bytes.starts_with(b"PK\x03\x04") {
+				return Self::read(Cursor::new(bytes), verify);
+			}
+
+			bytes = if bytes.starts_with(&[0x1f, 0x8b]) {
+				Cow::Owned(gunzip(&bytes, MAX_GUNZIPPED_SIZE)?)
+			} else {
+				let encoded = bytes
+					.iter()
+					.copied()
+					.filter(|byte| !byte.is_ascii_whitespace())
+					.collect::<Vec<_>>();
+				let decoded = STANDARD
+					.decode(encoded)
+					.map_err(|_| Error::UnknownArchiveFormat)?;
+				Cow::Owned(decoded)
+			};
+		}
+
+		Err(Error::UnknownArchiveFormat)
+	}
+
+	/// Read a `pkpass` file, checking it according to `options`.
+	///
+	/// # Errors
+	///
+	/// Fails if the archive is malformed, if an asset is not recognized or
+	/// if the signature or the manifest doesn't match the content.
+	#[cfg_attr(
+		feature = "tracing",
+		tracing::instrument(level = "debug", skip_all, fields(verify = %options.verify))
+	)]
+	pub fn read_with(reader: impl Read + Seek, options: ReadOptions) -> Result<Self> {
+		let ReadOptions {
+			verify,
+			verify_manifest_digests,
+			keep_unknown_assets,
+		} = options;
 		let mut zip = ZipArchive::new(reader)?;
+		let manifest = Self::read_manifest(&mut zip)?;
+		#[cfg(feature = "sign")]
+		let signer = Self::verify_signature(&mut zip, &manifest, verify.clone())?;
+		let manifest: Manifest = serde_json::from_slice(strip_bom(&manifest))?;
 
-		let signature = match zip.by_name("signature") {
-			Ok(mut file) => {
-				let mut signature = vec![];
-				file.read_to_end(&mut signature)?;
-				Some(Pkcs7::from_der(&signature)?)
+		let (metadata, deprecations) =
+			Self::read_pass_json(&mut zip, verify_manifest_digests.then_some(&manifest))?;
+
+		let mut assets = Assets::default();
+
+		for item in 0..zip.len() {
+			let mut item = zip.by_index(item)?;
+
+			if SPECIAL_FILES
+				.iter()
+				.any(|special| item.name().eq_ignore_ascii_case(special))
+			{
+				continue;
 			}
-			Err(ZipError::FileNotFound) => None,
-			Err(e) => return Err(e.into()),
+			// archives zipped on Windows may separate directories with backslashes
+			let name = item.name().replace('\\', "/");
+			check_asset_path(&name)?;
+			if !item.is_file() || name.ends_with('/') {
+				continue;
+			}
+
+			let mut data = vec![];
+			item.read_to_end(&mut data)?;
+			trace_event!(debug, file = name, size = data.len(), "read asset");
+
+			// first check if asset is a valid one
+			if !keep_unknown_assets {
+				assets.get_mut(&name)?;
+			}
+
+			// the manifest of such archives may list the path as is, too
+			if verify_manifest_digests
+				&& !manifest.verify_file(&name, &data)
+				&& !manifest.verify_file(item.name(), &data)
+			{
+				return Err(Error::ManifestSignatureMismatch(name));
+			}
+
+			assets.insert_raw(name, data);
+		}
+
+		let pass = Self {
+			metadata,
+			assets,
+			#[cfg(feature = "sign")]
+			signer,
+			deprecations,
 		};
 
-		// TODO: verify manifest based on sig
-		let manifest = match zip.by_name("manifest.json") {
+		if verify != VerifyMode::No {
+			let matched = pass.verify_identity_match();
+			trace_event!(debug, ok = matched.is_ok(), "verified identity match");
+			matched?;
+		}
+
+		Ok(pass)
+	}
+
+	/// Read only the `pass.json` of a `pkpass` file, verifying its signature according to `verify`.
+	///
+	/// Assets are neither loaded nor checked against the manifest, which is
+	/// much faster when indexing many passes. When verifying, the signature
+	/// is checked over the manifest and `pass.json` against its digest.
+	///
+	/// # Errors
+	///
+	/// Fails if the archive is malformed, if `pass.json` is missing or invalid
+	/// or if the signature or the manifest doesn't match it.
+	#[cfg_attr(not(feature = "sign"), allow(clippy::needless_pass_by_value))]
+	pub fn read_metadata_only(reader: impl Read + Seek, verify: VerifyMode) -> Result<Metadata> {
+		let mut zip = ZipArchive::new(reader)?;
+
+		if verify == VerifyMode::No {
+			return Ok(Self::read_pass_json(&mut zip, None)?.0);
+		}
+
+		let manifest = Self::read_manifest(&mut zip)?;
+		#[cfg(feature = "sign")]
+		let signer = Self::verify_signature(&mut zip, &manifest, verify)?;
+		let manifest: Manifest = serde_json::from_slice(strip_bom(&manifest))?;
+		let (metadata, deprecations) = Self::read_pass_json(&mut zip, Some(&manifest))?;
+		let pass = Self {
+			metadata,
+			assets: Assets::default(),
+			#[cfg(feature = "sign")]
+			signer,
+			deprecations,
+		};
+		pass.verify_identity_match()?;
+		Ok(pass.metadata)
+	}
+
+	/// Read the raw bytes of the manifest, which the signature covers
+	fn read_manifest(zip: &mut ZipArchive<impl Read + Seek>) -> Result<Vec<u8>> {
+		match zip.by_name(&special_file(zip, "manifest.json")) {
 			Ok(mut file) => {
 				let mut vec = vec![];
 				file.read_to_end(&mut vec)?;
-				vec
+				Ok(vec)
+			}
+			Err(ZipError::FileNotFound) => Err(Error::MissingFile("manifest.json")),
+			Err(e) => Err(e.into()),
+		}
+	}
+
+	/// Read the signer, verifying the signature of the `manifest` according to `verify`
+	#[cfg(feature = "sign")]
+	fn verify_signature(
+		zip: &mut ZipArchive<impl Read + Seek>,
+		manifest: &[u8],
+		verify: VerifyMode,
+	) -> Result<Option<X509>> {
+		let signature = match zip.by_name(&special_file(zip, "signature")) {
+			Ok(mut file) => {
+				let mut signature = vec![];
+				file.read_to_end(&mut signature)?;
+				Some(Pkcs7::from_der(&signature)?)
 			}
-			Err(ZipError::FileNotFound) => todo!(),
+			Err(ZipError::FileNotFound) => None,
 			Err(e) => return Err(e.into()),
 		};
+		trace_event!(debug, signed = signature.is_some(), "read signature");
 
-		match verify {
-			VerifyMode::No => {}
+		let mut pins = None;
+		let mut flags = Pkcs7Flags::empty();
+		let store = match verify {
+			VerifyMode::No => None,
 
 			#[cfg(feature = "apple")]
 			VerifyMode::Yes => {
-				if let Some(sig) = signature {
-					let stack = Stack::new()?;
-
-					let store = {
-						let mut store = X509StoreBuilder::new()?;
-						store.add_cert(certificates::apple_root())?;
-						store.add_cert(certificates::apple_wwdr_g4())?;
-						store.set_purpose(X509PurposeId::ANY)?;
-						store.build()
-					};
-
-					sig.verify(&stack, &store, Some(&manifest), None, Pkcs7Flags::empty())?;
-				}
+				let mut store = X509StoreBuilder::new()?;
+				store.add_cert(certificates::apple_root())?;
+				store.add_cert(certificates::apple_wwdr_g4())?;
+				store.set_purpose(X509PurposeId::ANY)?;
+				Some(Arc::new(store.build()))
 			}
-		}
 
-		let manifest: Manifest = serde_json::from_slice(&manifest)?;
+			VerifyMode::Custom(store) => Some(store),
 
-		let metadata: Metadata = match zip.by_name("pass.json") {
-			Ok(file) => serde_json::from_reader(file)?,
-			Err(ZipError::FileNotFound) => todo!(),
-			Err(e) => return Err(e.into()),
+			// the chain doesn't matter, only the signature and the signer fingerprint
+			VerifyMode::PinnedSigner(fingerprints) => {
+				pins = Some(fingerprints);
+				flags = Pkcs7Flags::NOVERIFY;
+				Some(Arc::new(X509StoreBuilder::new()?.build()))
+			}
 		};
 
-		let mut assets = Assets::default();
+		if let Some(store) = store {
+			// anyone can write an unsigned pass with a manifest matching its content
+			let Some(sig) = &signature else {
+				return Err(Error::MissingFile("signature"));
+			};
+			let stack = Stack::new()?;
+			let verified = sig.verify(&stack, &store, Some(manifest), None, flags);
+			trace_event!(debug, ok = verified.is_ok(), "verified signature");
+			verified?;
+		}
 
-		for item in 0..zip.len() {
-			let mut item = zip.by_index(item)?;
+		// kept for inspection only, verification already happened above if requested
+		let certs = Stack::new()?;
+		let signer = signature
+			.as_ref()
+			.and_then(|sig| sig.signers(&certs, Pkcs7Flags::empty()).ok())
+			.and_then(|mut signers| signers.pop());
 
-			if matches!(item.name(), "pass.json" | "manifest.json" | "signature") {
-				continue;
-			}
-			if !item.is_file() {
-				continue;
+		if let Some(pins) = pins {
+			let pinned = match &signer {
+				Some(signer) => pins.contains(&VerifyMode::fingerprint(signer)?),
+				None => false,
+			};
+			trace_event!(debug, pinned, "checked signer fingerprint");
+			if !pinned {
+				return Err(Error::SignerNotPinned);
 			}
+		}
 
-			let mut data = vec![];
-			item.read_to_end(&mut data)?;
+		Ok(signer)
+	}
 
-			// first check if asset is a valid one
-			let asset = assets.get_mut(item.name())?;
+	/// Read and parse `pass.json`, checking it against its digest when a `manifest` is given
+	fn read_pass_json(
+		zip: &mut ZipArchive<impl Read + Seek>,
+		manifest: Option<&Manifest>,
+	) -> Result<(Metadata, Vec<Deprecation>)> {
+		let mut metadata: Metadata = match zip.by_name(&special_file(zip, "pass.json")) {
+			Ok(mut file) => {
+				let mut pass_json = vec![];
+				file.read_to_end(&mut pass_json)?;
+				if manifest.is_some_and(|manifest| !manifest.verify_file("pass.json", &pass_json)) {
+					return Err(Error::ManifestSignatureMismatch("pass.json".into()));
+				}
+				let pass_json = strip_bom(&pass_json);
+				serde_json::from_slice(pass_json).map_err(|err| pass_json_error(pass_json, err))?
+			}
+			Err(ZipError::FileNotFound) => return Err(Error::MissingFile("pass.json")),
+			Err(e) => return Err(e.into()),
+		};
 
-			if !manifest.verify_file(item.name(), &data) {
-				return Err(Error::ManifestSignatureMismatch(item.name().into()));
+		let mut deprecations = vec![];
+		// iOS 8 and earlier only know a single barcode, later versions ignore it when `barcodes` is set
+		#[allow(deprecated)]
+		if let Some(barcode) = metadata.barcode.take() {
+			if metadata.barcodes.is_empty() {
+				metadata.barcodes.push(barcode);
 			}
+			deprecations.push(Deprecation::Barcode);
+		}
 
-			let _ = mem::replace(asset, data);
+		if metadata.format_version != 1 {
+			return Err(Error::UnsupportedFormatVersion(metadata.format_version));
 		}
 
-		Ok(Self { metadata, assets })
+		Ok((metadata, deprecations))
 	}
 
 	/// Bundle a pass to a `pkpass` file.
@@ -147,17 +678,76 @@ impl Pass {
 	///     .truncate(true)
 	///     .open("custom.pkpass")?;
 	///
-	/// pass.write(identity, file)?;
+	/// pass.write(&identity, file)?;
 	/// # Ok(())}
 	/// ```
-	pub fn write(&self, identity: Identity, writer: impl Write + Seek) -> Result<()> {
+	///
+	/// # Errors
+	///
+	/// Fails if the metadata doesn't have exactly one pass style, if the
+	/// archive could not be written or the manifest could not be signed.
+	pub fn write(&self, identity: &Identity, writer: impl Write + Seek) -> Result<()> {
+		self.write_with(identity, writer, WriteOptions::default())?;
+		Ok(())
+	}
+
+	/// Write the pass like [`Pass::write`], according to `options`, returning
+	/// the size of the archive in bytes.
+	///
+	/// # Errors
+	///
+	/// Fails for the same reasons as [`Pass::write`], or with
+	/// [`Error::PassTooLarge`] if the archive exceeds [`WriteOptions::max_size`].
+	pub fn write_with(
+		&self,
+		identity: &Identity,
+		writer: impl Write + Seek,
+		options: WriteOptions,
+	) -> Result<u64> {
+		let assets = self
+			.assets
+			.paths()
+			.into_iter()
+			.map(|(path, content)| Ok((path, Cow::Borrowed(content.as_slice()))));
+		self.write_archive(identity, assets, writer, options)
+	}
+
+	/// Write the archive with `assets` as they come, so they don't all need to
+	/// be in memory, returning its size
+	pub(crate) fn write_archive<'a>(
+		&self,
+		identity: &Identity,
+		assets: impl IntoIterator<Item = Result<(String, Cow<'a, [u8]>)>>,
+		writer: impl Write + Seek,
+		options: WriteOptions,
+	) -> Result<u64> {
+		let mut writer = SizeBudget::new(writer, options.max_size)?;
+		let written = self.write_entries(identity, assets, &mut writer, options.manifest_hash);
+		if let (Some(max_size), true) = (options.max_size, writer.exceeded) {
+			return Err(Error::PassTooLarge(max_size));
+		}
+		written?;
+		Ok(writer.size)
+	}
+
+	fn write_entries<'a>(
+		&self,
+		identity: &Identity,
+		assets: impl IntoIterator<Item = Result<(String, Cow<'a, [u8]>)>>,
+		writer: impl Write + Seek,
+		manifest_hash: ManifestHashPolicy,
+	) -> Result<()> {
+		self.metadata.check_kind()?;
+
 		// TODO: no cloning nor mutation should happen here
 		let mut metadata = self.metadata.clone();
-		metadata.pass_type_identifier = identity.pass_type_id;
-		metadata.team_identifier = identity.team_id;
+		metadata
+			.pass_type_identifier
+			.clone_from(&identity.pass_type_id);
+		metadata.team_identifier.clone_from(&identity.team_id);
 		// ---ugly---
 
-		let mut manifest = Manifest::default();
+		let mut manifest = Manifest::new(manifest_hash);
 
 		let mut zip = zip::ZipWriter::new(writer);
 		let options =
@@ -168,31 +758,1755 @@ impl Pass {
 		zip.start_file("pass.json", options)?;
 		zip.write_all(&pass_data)?;
 
-		for (asset_path, asset_content) in self.assets.paths() {
-			manifest.add_file(&asset_path, asset_content);
+		for asset in assets {
+			let (asset_path, asset_content) = asset?;
+			manifest.add_file(&asset_path, &asset_content);
 			zip.start_file(asset_path, options)?;
-			zip.write_all(asset_content)?;
+			zip.write_all(&asset_content)?;
 		}
 
 		let manifest_data = serde_json::to_vec(&manifest)?;
 		zip.start_file("manifest.json", options)?;
 		zip.write_all(&manifest_data)?;
 
-		if let Some(pen) = &identity.pen {
-			let signature = Pkcs7::sign(
-				&pen.signer_certificate,
-				&pen.signer_private_key,
-				&pen.chain,
-				&manifest_data,
-				Pkcs7Flags::DETACHED,
-			)?;
-
+		if let Some(signer) = identity.signer() {
+			let signature = signer.sign(&manifest_data)?;
 			zip.start_file("signature", options)?;
-			zip.write_all(&signature.to_der()?)?;
+			zip.write_all(&signature)?;
 		}
 
 		zip.finish()?;
 
 		Ok(())
 	}
+
+	/// Bundle a pass to a writer that can't seek, such as a pipe, a socket or stdout.
+	///
+	/// Zip archives are written by seeking back to fill entry headers, so the
+	/// whole archive is first assembled in memory, then copied to `writer`.
+	/// Memory usage is about the size of the resulting `pkpass` file, mostly
+	/// made of the assets. Prefer [`Pass::write`] when the writer can seek.
+	///
+	/// # Errors
+	///
+	/// Fails if the archive could not be written or the manifest could not be signed.
+	pub fn write_to(&self, identity: &Identity, mut writer: impl Write) -> Result<()> {
+		let mut buffer = Cursor::new(vec![]);
+		self.write(identity, &mut buffer)?;
+		writer.write_all(buffer.get_ref())?;
+		writer.flush()?;
+		Ok(())
+	}
+
+	/// Write `pass.json` as stored in archives, to sign the pass with other tools
+	///
+	/// Unlike [`Pass::write`], the pass type and team identifiers are written
+	/// as set in the metadata, they must match the signing certificate.
+	///
+	/// # Errors
+	///
+	/// Fails if the pass kind is invalid or if `pass.json` could not be written.
+	pub fn write_pass_json(&self, mut writer: impl Write) -> Result<()> {
+		self.metadata.check_kind()?;
+		writer.write_all(&serde_json::to_vec(&self.metadata)?)?;
+		Ok(())
+	}
+
+	/// Write the `manifest.json` of `pass.json` and the assets, as written by
+	/// [`Pass::write_pass_json`] and [`Pass::write_assets_to_dir`], with the
+	/// digest [`WriteOptions::manifest_hash`] would pick
+	///
+	/// # Errors
+	///
+	/// Fails if the pass kind is invalid or if the manifest could not be written.
+	pub fn write_manifest(&self, mut writer: impl Write, hash: ManifestHashPolicy) -> Result<()> {
+		self.metadata.check_kind()?;
+		let manifest = self.manifest(&serde_json::to_vec(&self.metadata)?, hash);
+		writer.write_all(&serde_json::to_vec(&manifest)?)?;
+		Ok(())
+	}
+
+	/// Write every asset under `dir` with its path in the archive, such as
+	/// `fr.lproj/logo@2x.png`, creating directories as needed
+	///
+	/// # Errors
+	///
+	/// Fails if a directory or a file could not be written.
+	pub fn write_assets_to_dir(&self, dir: impl AsRef<Path>) -> Result<()> {
+		for (asset_path, asset_content) in self.assets.paths() {
+			let path = dir.as_ref().join(asset_path);
+			if let Some(parent) = path.parent() {
+				fs::create_dir_all(parent)?;
+			}
+			fs::write(path, asset_content)?;
+		}
+		Ok(())
+	}
+
+	/// Manifest of the assets and the given `pass.json`
+	fn manifest(&self, pass_json: &[u8], hash: ManifestHashPolicy) -> Manifest {
+		let mut manifest = Manifest::new(hash);
+		manifest.add_file("pass.json", pass_json);
+		for (asset_path, asset_content) in self.assets.paths() {
+			manifest.add_file(&asset_path, asset_content);
+		}
+		manifest
+	}
+
+	/// Re-issue a pass after editing it in place, such as with [`Metadata::field_mut`].
+	///
+	/// The manifest and the signature are recomputed from the current content
+	/// whoever signed the pass before, exactly like [`Pass::write`] does.
+	///
+	/// # Errors
+	///
+	/// Fails if the metadata doesn't have exactly one pass style, if the
+	/// archive could not be written or the manifest could not be signed.
+	pub fn resign(&self, identity: &Identity, writer: impl Write + Seek) -> Result<()> {
+		self.write(identity, writer)
+	}
+}
+
+/// Files of the archive that are not assets
+const SPECIAL_FILES: [&str; 3] = ["pass.json", "manifest.json", "signature"];
+
+/// Name of a special file in the archive, matched regardless of case as
+/// some generators write `Manifest.json` or `pass.JSON`
+fn special_file(zip: &ZipArchive<impl Read + Seek>, name: &'static str) -> String {
+	if zip.index_for_name(name).is_some() {
+		return name.into();
+	}
+
+	let found = zip
+		.file_names()
+		.find(|file| file.eq_ignore_ascii_case(name))
+		.unwrap_or(name);
+	if found != name {
+		trace_event!(
+			warn,
+			file = found,
+			expected = name,
+			"special file has the wrong case"
+		);
+	}
+	found.into()
+}
+
+/// Bound to the size of a gzip layer unwrapped by [`Pass::read_auto`], far
+/// above any real pass, so that a small gzip bomb cannot exhaust the memory
+const MAX_GUNZIPPED_SIZE: u64 = 64 * 1024 * 1024;
+
+/// Decompress gzip `bytes`, failing with [`Error::PassTooLarge`] rather than
+/// inflate more than `limit` bytes
+fn gunzip(bytes: &[u8], limit: u64) -> Result<Vec<u8>> {
+	let mut output = vec![];
+	// one byte past the limit tells a stream of exactly `limit` bytes from a larger one
+	flate2::read::GzDecoder::new(bytes)
+		.take(limit.saturating_add(1))
+		.read_to_end(&mut output)?;
+	if output.len() as u64 > limit {
+		return Err(Error::PassTooLarge(limit));
+	}
+	Ok(output)
+}
+
+/// Reject paths that would escape a directory the assets are written to,
+/// such as `../../etc/passwd`, `/etc/passwd` or `C:\Windows`
+fn check_asset_path(path: &str) -> Result<()> {
+	let mut components = path.split(['/', '\\']);
+	let absolute = path.starts_with(['/', '\\'])
+		|| components
+			.clone()
+			.next()
+			.is_some_and(|first| first.contains(':'));
+	if absolute || components.any(|component| component == "..") {
+		return Err(Error::UnsafeAssetPath(path.into()));
+	}
+	Ok(())
+}
+
+/// Writer refusing to grow past `max` bytes from where it started, keeping
+/// track of the size written
+struct SizeBudget<W> {
+	inner: W,
+	start: u64,
+	position: u64,
+	size: u64,
+	max: Option<u64>,
+	exceeded: bool,
+}
+
+impl<W: Seek> SizeBudget<W> {
+	fn new(mut inner: W, max: Option<u64>) -> io::Result<Self> {
+		let start = inner.stream_position()?;
+		Ok(Self {
+			inner,
+			start,
+			position: 0,
+			size: 0,
+			max,
+			exceeded: false,
+		})
+	}
+}
+
+impl<W: Write> Write for SizeBudget<W> {
+	fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+		let end = self.position + buf.len() as u64;
+		if self.max.is_some_and(|max| end > max) {
+			self.exceeded = true;
+			return Err(io::Error::other("archive exceeds its size budget"));
+		}
+		let written = self.inner.write(buf)?;
+		self.position += written as u64;
+		self.size = self.size.max(self.position);
+		Ok(written)
+	}
+
+	fn flush(&mut self) -> io::Result<()> {
+		self.inner.flush()
+	}
+}
+
+impl<W: Seek> Seek for SizeBudget<W> {
+	fn seek(&mut self, pos: io::SeekFrom) -> io::Result<u64> {
+		let absolute = self.inner.seek(pos)?;
+		self.position = absolute.saturating_sub(self.start);
+		Ok(absolute)
+	}
+}
+
+/// Single out unknown top-level keys of `pass.json` from other parsing errors
+///
+/// Unknown keys nested in fields or semantic tags are left as json errors,
+/// they don't hint at a newer format of the whole pass.
+fn pass_json_error(pass_json: &[u8], err: serde_json::Error) -> Error {
+	let Ok(serde_json::Value::Object(object)) = serde_json::from_slice(pass_json) else {
+		return err.into();
+	};
+	let known = metadata_keys();
+	object
+		.keys()
+		.find(|key| !known.contains(&key.as_str()))
+		.map_or_else(|| err.into(), |key| Error::UnknownPassKey(key.clone()))
+}
+
+/// Keys of `pass.json` known to [`Metadata`], as listed by its `Deserialize` impl
+fn metadata_keys() -> &'static [&'static str] {
+	use serde::de::{self, Deserialize, Visitor};
+
+	/// Deserializer only recording the fields of the struct asked for
+	struct Fields(&'static [&'static str]);
+
+	impl<'de> de::Deserializer<'de> for &mut Fields {
+		type Error = de::value::Error;
+
+		fn deserialize_any<V: Visitor<'de>>(
+			self,
+			_: V,
+		) -> std::result::Result<V::Value, Self::Error> {
+			Err(de::Error::custom("only structs are supported"))
+		}
+
+		fn deserialize_struct<V: Visitor<'de>>(
+			self,
+			_: &'static str,
+			fields: &'static [&'static str],
+			_: V,
+		) -> std::result::Result<V::Value, Self::Error> {
+			self.0 = fields;
+			Err(de::Error::custom("fields recorded"))
+		}
+
+		serde::forward_to_deserialize_any! {
+			bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+			bytes byte_buf option unit unit_struct newtype_struct seq tuple
+			tuple_struct map enum identifier ignored_any
+		}
+	}
+
+	let mut fields = Fields(&[]);
+	let _ = Metadata::deserialize(&mut fields);
+	fields.0
+}
+
+/// Skip the UTF-8 byte order mark some Windows editors prepend to json files
+fn strip_bom(json: &[u8]) -> &[u8] {
+	json.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(json)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::models::{
+		Barcode, BarcodeFormat, Fields, LocalizableString, SemanticEventType, SemanticTags,
+		StyleScheme,
+	};
+	use crate::testing::{config, identity, minimal_pass_json};
+	#[cfg(feature = "sign")]
+	use crate::{
+		models::Field,
+		sign::{Signer, SigningPen},
+	};
+	use chrono::{TimeZone, Utc};
+	#[cfg(feature = "sign")]
+	use openssl::x509::{store::X509StoreBuilder, X509PurposeId};
+	#[cfg(feature = "sign")]
+	use std::sync::Arc;
+
+	/// Build an unsigned archive from raw entries, with a matching manifest
+	fn archive(entries: &[(&str, &[u8])]) -> Result<Vec<u8>> {
+		let manifest = manifest_json(entries)?;
+		zip_archive(&[entries, &[("manifest.json", &manifest)]].concat())
+	}
+
+	/// Build an archive from raw entries as they are, which may omit the
+	/// manifest or list other files in it, see [`manifest_json`]
+	fn zip_archive(entries: &[(&str, &[u8])]) -> Result<Vec<u8>> {
+		let mut zip = zip::ZipWriter::new(Cursor::new(vec![]));
+		for (name, data) in entries {
+			zip.start_file(*name, SimpleFileOptions::default())?;
+			zip.write_all(data)?;
+		}
+		Ok(zip.finish()?.into_inner())
+	}
+
+	/// Serialize a manifest with the digests of the `listed` files
+	fn manifest_json(listed: &[(&str, &[u8])]) -> Result<Vec<u8>> {
+		let mut manifest = Manifest::default();
+		for (name, data) in listed {
+			manifest.add_file(name, data);
+		}
+		Ok(serde_json::to_vec(&manifest)?)
+	}
+
+	/// Rewrite every entry of an archive with another compression method
+	#[cfg(feature = "sign")]
+	fn recompress(archive: &[u8], method: zip::CompressionMethod) -> Result<Vec<u8>> {
+		let mut input = ZipArchive::new(Cursor::new(archive))?;
+		let mut output = zip::ZipWriter::new(Cursor::new(vec![]));
+		let options = SimpleFileOptions::default().compression_method(method);
+		for index in 0..input.len() {
+			let mut file = input.by_index(index)?;
+			let mut data = vec![];
+			file.read_to_end(&mut data)?;
+			output.start_file(file.name(), options)?;
+			output.write_all(&data)?;
+		}
+		Ok(output.finish()?.into_inner())
+	}
+
+	/// Rewrite an archive with the content of the entry `name` replaced by `data`
+	#[cfg(feature = "sign")]
+	fn replace_entry(archive: &[u8], name: &str, data: &[u8]) -> Result<Vec<u8>> {
+		let mut input = ZipArchive::new(Cursor::new(archive))?;
+		let mut output = zip::ZipWriter::new(Cursor::new(vec![]));
+		for index in 0..input.len() {
+			let file = input.by_index(index)?;
+			if file.name() == name {
+				output.start_file(name, SimpleFileOptions::default())?;
+				output.write_all(data)?;
+			} else {
+				output.raw_copy_file(file)?;
+			}
+		}
+		Ok(output.finish()?.into_inner())
+	}
+
+	fn pass_json(patch: impl FnOnce(&mut serde_json::Value)) -> Result<Vec<u8>> {
+		let mut value = serde_json::to_value(Pass::new(config()).metadata)?;
+		patch(&mut value);
+		Ok(serde_json::to_vec(&value)?)
+	}
+
+	fn round_trip(pass: &Pass) -> Result<Pass> {
+		let mut archive = Cursor::new(vec![]);
+		pass.write(&identity(), &mut archive)?;
+		archive.set_position(0);
+		Pass::read(archive, VerifyMode::No)
+	}
+
+	#[test]
+	#[cfg(feature = "sign")]
+	fn public_types_are_send_sync() -> Result<()> {
+		const fn assert_send_sync<T: Send + Sync>() {}
+		assert_send_sync::<Pass>();
+		assert_send_sync::<Metadata>();
+		assert_send_sync::<Assets>();
+		assert_send_sync::<Identity>();
+		assert_send_sync::<sign::SigningPen>();
+		assert_send_sync::<template::Template>();
+		assert_send_sync::<Error>();
+
+		let (identity, _) = testing::self_signed("pass.com.acme", "ACME123456")?;
+		let identity = std::sync::Arc::new(identity);
+		let pass = std::sync::Arc::new(Pass::new(config()));
+
+		let writer = || {
+			let (identity, pass) = (identity.clone(), pass.clone());
+			std::thread::spawn(move || pass.write(&identity, Cursor::new(vec![])))
+		};
+		for handle in [writer(), writer()] {
+			handle.join().expect("writer thread panicked")?;
+		}
+
+		Ok(())
+	}
+
+	#[test]
+	fn pass_with_colors() -> std::result::Result<(), Box<dyn std::error::Error>> {
+		let pass = Pass::new(config())
+			.with_foreground_color("rgb(255, 255, 255)")?
+			.with_background_color("rgb(23,187,82)")?
+			.with_label_color("#0a0b0c")?;
+
+		let json = serde_json::to_value(&pass.metadata)?;
+		assert_eq!(json["foregroundColor"], "rgb(255,255,255)");
+		assert_eq!(json["backgroundColor"], "rgb(23,187,82)");
+		assert_eq!(json["labelColor"], "rgb(10,11,12)");
+
+		assert!(Pass::new(config()).with_label_color("blue").is_err());
+
+		Ok(())
+	}
+
+	#[test]
+	fn metadata_pretty_json() -> Result<()> {
+		let mut pass = Pass::new(config());
+		pass.metadata.logo_text = Some("Acme".into());
+
+		let json = pass.metadata.to_pretty_json()?;
+		assert!(json.starts_with("{\n  \"description\": \"A test pass\",\n"));
+		assert!(json.find("\"logoText\"") < json.find("\"organizationName\""));
+		assert_eq!(json, pass.metadata.to_pretty_json()?);
+
+		Ok(())
+	}
+
+	#[test]
+	fn round_trip_sports_semantics() -> Result<()> {
+		let start = Utc.with_ymd_and_hms(2024, 7, 22, 20, 0, 0).unwrap();
+
+		let mut pass = Pass::new(PassConfig {
+			kind: PassKind::EventTicket(Fields::default()),
+			..config()
+		});
+		pass.metadata.semantics = Some(SemanticTags {
+			event_type: Some(SemanticEventType::Sports),
+			home_team_name: Some("Paris Saint-Germain".into()),
+			away_team_name: Some("Olympique de Marseille".into()),
+			league_name: Some("Ligue 1".into()),
+			event_start_date: Some(start),
+			..SemanticTags::default()
+		});
+
+		let pass = round_trip(&pass)?;
+		let semantics = pass.metadata.semantics.expect("semantics were written");
+
+		assert!(matches!(
+			semantics.event_type,
+			Some(SemanticEventType::Sports)
+		));
+		assert_eq!(
+			semantics.home_team_name.as_deref(),
+			Some("Paris Saint-Germain")
+		);
+		assert_eq!(
+			semantics.away_team_name.as_deref(),
+			Some("Olympique de Marseille")
+		);
+		assert_eq!(semantics.league_name.as_deref(), Some("Ligue 1"));
+		assert_eq!(semantics.event_start_date, Some(start));
+
+		Ok(())
+	}
+
+	#[test]
+	fn round_trip_localized_barcode_alt_text() -> std::result::Result<(), Box<dyn std::error::Error>>
+	{
+		let mut pass = Pass::new(config());
+		pass.metadata.barcodes.push(Barcode {
+			format: BarcodeFormat::Qr,
+			message: "1234".into(),
+			message_encoding: "iso-8859-1".into(),
+			alt_text: Some("barcode.alt".into()),
+		});
+
+		let (en, fr) = ("en".parse()?, "fr".parse()?);
+		pass.assets
+			.get(en)
+			.insert_string("barcode.alt", "Ticket 1234");
+		pass.assets
+			.get(fr)
+			.insert_string("barcode.alt", "Billet 1234");
+
+		let pass = round_trip(&pass)?;
+		let alt_text = pass.metadata.barcodes[0]
+			.alt_text
+			.as_ref()
+			.expect("alt text was written");
+
+		assert_eq!(alt_text, &LocalizableString::from("barcode.alt"));
+		assert_eq!(
+			alt_text.localize(pass.assets.localized(&"en".parse()?)),
+			"Ticket 1234"
+		);
+		assert_eq!(
+			alt_text.localize(pass.assets.localized(&"fr".parse()?)),
+			"Billet 1234"
+		);
+		assert_eq!(
+			alt_text.localize(pass.assets.localized(&"de".parse()?)),
+			"barcode.alt"
+		);
+
+		Ok(())
+	}
+
+	#[test]
+	#[cfg(feature = "tracing")]
+	fn trace_read() -> Result<()> {
+		use std::sync::{Arc, Mutex};
+		use tracing::{field, span, Event, Metadata, Subscriber};
+
+		/// Record every event as its `key=value` fields
+		#[derive(Clone, Default)]
+		struct Recorder(Arc<Mutex<Vec<String>>>);
+
+		struct Line(Vec<String>);
+
+		impl field::Visit for Line {
+			fn record_debug(&mut self, field: &field::Field, value: &dyn std::fmt::Debug) {
+				self.0.push(format!("{}={value:?}", field.name()));
+			}
+		}
+
+		impl Subscriber for Recorder {
+			fn enabled(&self, _: &Metadata<'_>) -> bool {
+				true
+			}
+			fn new_span(&self, _: &span::Attributes<'_>) -> span::Id {
+				span::Id::from_u64(1)
+			}
+			fn record(&self, _: &span::Id, _: &span::Record<'_>) {}
+			fn record_follows_from(&self, _: &span::Id, _: &span::Id) {}
+			fn event(&self, event: &Event<'_>) {
+				let mut line = Line(vec![]);
+				event.record(&mut line);
+				let line = line.0.join(" ");
+				self.0.lock().expect("recorder is not poisoned").push(line);
+			}
+			fn enter(&self, _: &span::Id) {}
+			fn exit(&self, _: &span::Id) {}
+		}
+
+		let mut pass = Pass::new(config());
+		pass.assets.images.icon.size_x1 = Some(b"icon".to_vec());
+		let mut archive = Cursor::new(vec![]);
+		pass.write(&identity(), &mut archive)?;
+		archive.set_position(0);
+
+		let recorder = Recorder::default();
+		tracing::subscriber::with_default(recorder.clone(), || {
+			Pass::read(archive, VerifyMode::No)
+		})?;
+
+		let events = recorder.0.lock().expect("recorder is not poisoned").clone();
+		// the signature is only read when it can be verified
+		#[cfg(feature = "sign")]
+		assert!(
+			events.iter().any(|e| e.contains("signed=false")),
+			"{events:?}"
+		);
+		assert!(
+			events
+				.iter()
+				.any(|e| e.contains("file=\"icon.png\"") && e.contains("size=4")),
+			"{events:?}"
+		);
+		assert!(
+			events
+				.iter()
+				.any(|e| e.contains("digest=") && e.contains("ok=true")),
+			"{events:?}"
+		);
+
+		Ok(())
+	}
+
+	#[test]
+	fn content_hash_ignores_identity_and_order() -> Result<()> {
+		let mut pass = Pass::new(config());
+		pass.assets.images.icon.size_x1 = Some(b"icon".to_vec());
+		pass.assets.images.logo.size_x2 = Some(b"logo".to_vec());
+
+		let mut reordered = Pass::new(config());
+		reordered.assets.images.logo.size_x2 = Some(b"logo".to_vec());
+		reordered.assets.images.icon.size_x1 = Some(b"icon".to_vec());
+
+		let read = round_trip(&pass)?;
+		assert_eq!(read.metadata.pass_type_identifier(), "pass.com.acme");
+		assert_eq!(pass.content_hash()?, reordered.content_hash()?);
+		assert_eq!(pass.content_hash()?, read.content_hash()?);
+
+		reordered.assets.images.logo.size_x2 = Some(b"other logo".to_vec());
+		assert_ne!(pass.content_hash()?, reordered.content_hash()?);
+
+		// stable across releases and features, such as `serde_json/preserve_order`
+		assert_eq!(
+			hex::encode(pass.content_hash()?),
+			"79bb72c42647faae0752512f7b757f611ea9e59790a429f0980562ce999e03a2"
+		);
+
+		Ok(())
+	}
+
+	#[cfg(feature = "sign")]
+	fn verify_signature(archive: &[u8], anchor: &X509) -> Result<()> {
+		let mut zip = ZipArchive::new(Cursor::new(archive))?;
+
+		let mut signature = vec![];
+		zip.by_name("signature")?.read_to_end(&mut signature)?;
+		let mut manifest = vec![];
+		zip.by_name("manifest.json")?.read_to_end(&mut manifest)?;
+
+		let store = {
+			let mut store = X509StoreBuilder::new()?;
+			store.add_cert(anchor.clone())?;
+			store.set_purpose(X509PurposeId::ANY)?;
+			store.build()
+		};
+
+		let certs = Stack::new()?;
+		Pkcs7::from_der(&signature)?.verify(
+			&certs,
+			&store,
+			Some(&manifest),
+			None,
+			Pkcs7Flags::empty(),
+		)?;
+		Ok(())
+	}
+
+	#[test]
+	#[cfg(feature = "apple")]
+	fn signature_embeds_wwdr_chain() -> Result<()> {
+		let wwdr = certificates::apple_wwdr_g4();
+		let embedded = |identity: &Identity| -> Result<Vec<X509>> {
+			let mut archive = Cursor::new(vec![]);
+			Pass::new(config()).write(identity, &mut archive)?;
+
+			let mut zip = ZipArchive::new(archive)?;
+			let mut signature = vec![];
+			zip.by_name("signature")?.read_to_end(&mut signature)?;
+
+			let signature = Pkcs7::from_der(&signature)?;
+			let certs = signature.signed().and_then(|signed| signed.certificates());
+			Ok(certs.into_iter().flatten().map(X509Ref::to_owned).collect())
+		};
+
+		let (identity, cert) =
+			testing::issued_by("pass.com.acme", "ACME123456", Some(wwdr.subject_name()))?;
+		assert!(identity
+			.pen
+			.as_ref()
+			.is_some_and(|pen| pen.chain.is_empty()));
+		assert_eq!(embedded(&identity)?, [cert, wwdr.clone()]);
+
+		// appended to a chain lacking it, but never twice
+		let (mut identity, _) =
+			testing::issued_by("pass.com.acme", "ACME123456", Some(wwdr.subject_name()))?;
+		let pen = identity.pen.as_mut().expect("identity signs");
+		pen.chain.push(certificates::apple_root())?;
+		let embedded_chain = embedded(&identity)?;
+		assert_eq!(embedded_chain.len(), 3);
+		assert!(embedded_chain.contains(&certificates::apple_root()));
+		assert!(embedded_chain.contains(&wwdr));
+
+		let pen = identity.pen.as_mut().expect("identity signs");
+		pen.chain.push(wwdr)?;
+		assert_eq!(embedded(&identity)?.len(), 3);
+
+		// only certificates issued by Apple get the WWDR intermediate
+		let (identity, cert) = testing::self_signed("pass.com.acme", "ACME123456")?;
+		assert_eq!(embedded(&identity)?, [cert]);
+
+		Ok(())
+	}
+
+	#[test]
+	#[cfg(feature = "sign")]
+	fn key_cert_mismatch() -> Result<()> {
+		let (mut identity, _) = testing::self_signed("pass.com.acme", "ACME123456")?;
+		let (other, _) = testing::self_signed("pass.com.acme", "ACME123456")?;
+		let pen = identity.pen.as_mut().expect("identity signs");
+		pen.signer_private_key = other.pen.expect("identity signs").signer_private_key;
+
+		let result = Pass::new(config()).write(&identity, Cursor::new(vec![]));
+		assert!(matches!(result, Err(Error::KeyCertMismatch)));
+
+		Ok(())
+	}
+
+	#[test]
+	#[cfg(feature = "sign")]
+	fn signature_flags() -> Result<()> {
+		let (identity, cert) = testing::self_signed("pass.com.acme", "ACME123456")?;
+		let pen = identity.pen.as_ref().expect("identity signs");
+		assert_eq!(pen.flags, SigningPen::DEFAULT_FLAGS);
+
+		let mut binary = Cursor::new(vec![]);
+		Pass::new(config()).write(&identity, &mut binary)?;
+		verify_signature(binary.get_ref(), &cert)?;
+
+		let (identity, cert) = testing::self_signed("pass.com.acme", "ACME123456")?;
+		let identity = Identity {
+			pen: identity.pen.map(|pen| pen.with_flags(Pkcs7Flags::empty())),
+			..identity
+		};
+		let mut text = Cursor::new(vec![]);
+		Pass::new(config()).write(&identity, &mut text)?;
+		verify_signature(text.get_ref(), &cert)?;
+
+		// a manifest with a newline only verifies when signed as binary
+		let pen = identity.pen.as_ref().expect("identity signs");
+		let manifest = b"{\n}";
+		let certs = Stack::new()?;
+		let store = {
+			let mut store = X509StoreBuilder::new()?;
+			store.add_cert(cert)?;
+			store.set_purpose(X509PurposeId::ANY)?;
+			store.build()
+		};
+		let verify = |flags| -> Result<()> {
+			let signature = Pkcs7::sign(
+				&pen.signer_certificate,
+				&pen.signer_private_key,
+				&pen.chain,
+				manifest,
+				flags,
+			)?;
+			signature.verify(&certs, &store, Some(manifest), None, Pkcs7Flags::empty())?;
+			Ok(())
+		};
+		verify(SigningPen::DEFAULT_FLAGS)?;
+		assert!(verify(Pkcs7Flags::DETACHED).is_err());
+
+		Ok(())
+	}
+
+	#[test]
+	#[cfg(feature = "sign")]
+	fn resign_with_new_identity() -> Result<()> {
+		let (old_identity, old_cert) = testing::self_signed("pass.com.old", "OLD1234567")?;
+		let (new_identity, new_cert) = testing::self_signed("pass.com.acme", "ACME123456")?;
+
+		let mut signed = Cursor::new(vec![]);
+		Pass::new(config()).write(&old_identity, &mut signed)?;
+		signed.set_position(0);
+
+		let mut pass = Pass::read(signed, VerifyMode::No)?;
+		assert_eq!(pass.signer().map(X509Ref::to_owned), Some(old_cert.clone()));
+
+		pass.strip_signature();
+		assert!(pass.signer().is_none());
+
+		let mut resigned = Cursor::new(vec![]);
+		pass.write(&new_identity, &mut resigned)?;
+		let resigned = resigned.into_inner();
+
+		verify_signature(&resigned, &new_cert)?;
+		assert!(verify_signature(&resigned, &old_cert).is_err());
+
+		let pass = Pass::read(Cursor::new(resigned), VerifyMode::No)?;
+		assert_eq!(pass.signer().map(X509Ref::to_owned), Some(new_cert));
+		assert_eq!(pass.metadata.pass_type_identifier(), "pass.com.acme");
+		assert_eq!(pass.metadata.team_identifier(), "ACME123456");
+
+		Ok(())
+	}
+
+	#[test]
+	fn reject_unsupported_format_version() -> Result<()> {
+		let pass = pass_json(|json| json["formatVersion"] = 2.into())?;
+		let archive = archive(&[("pass.json", &pass)])?;
+
+		let result = Pass::read(Cursor::new(archive), VerifyMode::No);
+		assert!(matches!(result, Err(Error::UnsupportedFormatVersion(2))));
+
+		Ok(())
+	}
+
+	#[test]
+	#[cfg(feature = "sign")]
+	fn read_deflated_pass() -> Result<()> {
+		let (identity, cert) = testing::self_signed("pass.com.acme", "ACME123456")?;
+
+		let mut pass = Pass::new(config());
+		pass.assets.images.icon.size_x1 = Some(vec![0; 1024]);
+
+		let mut stored = Cursor::new(vec![]);
+		pass.write(&identity, &mut stored)?;
+
+		let deflated = recompress(stored.get_ref(), zip::CompressionMethod::Deflated)?;
+		assert!(deflated.len() < stored.get_ref().len());
+		let mut zip = ZipArchive::new(Cursor::new(&deflated))?;
+		for name in ["pass.json", "manifest.json", "signature", "icon.png"] {
+			let compression = zip.by_name(name)?.compression();
+			assert_eq!(compression, zip::CompressionMethod::Deflated);
+		}
+
+		verify_signature(&deflated, &cert)?;
+		let pass = Pass::read(Cursor::new(deflated), VerifyMode::No)?;
+		assert_eq!(pass.signer().map(X509Ref::to_owned), Some(cert));
+		assert_eq!(pass.assets.images.icon.size_x1, Some(vec![0; 1024]));
+
+		Ok(())
+	}
+
+	#[test]
+	fn metadata_getters() -> Result<()> {
+		let pass = round_trip(&Pass::new(config()))?;
+
+		assert_eq!(pass.metadata.format_version(), 1);
+		assert_eq!(pass.metadata.pass_type_identifier(), "pass.com.acme");
+		assert_eq!(pass.metadata.team_identifier(), "ACME123456");
+		assert_eq!(pass.metadata.description(), "A test pass");
+		assert_eq!(pass.metadata.serial_number(), "1234");
+
+		Ok(())
+	}
+
+	#[test]
+	fn write_requires_one_pass_kind() -> Result<()> {
+		let two_kinds = pass_json(|pass| pass["storeCard"] = serde_json::json!({}))?;
+		let pass = Pass::from_raw_parts(serde_json::from_slice(&two_kinds)?, Assets::default());
+		assert!(matches!(
+			pass.write(&identity(), Cursor::new(vec![])),
+			Err(Error::MultiplePassKinds(kinds)) if kinds == ["generic", "storeCard"]
+		));
+
+		let no_kind = pass_json(|pass| pass["generic"] = serde_json::Value::Null)?;
+		let pass = Pass::from_raw_parts(serde_json::from_slice(&no_kind)?, Assets::default());
+		assert!(matches!(
+			pass.write(&identity(), Cursor::new(vec![])),
+			Err(Error::MissingPassKind)
+		));
+
+		Ok(())
+	}
+
+	#[test]
+	fn read_deprecated_barcode() -> Result<()> {
+		let barcode = serde_json::json!({
+			"format": "PKBarcodeFormatQR",
+			"message": "1234",
+			"messageEncoding": "iso-8859-1",
+		});
+		let read = |pass_json: Vec<u8>| {
+			Pass::read(
+				Cursor::new(archive(&[("pass.json", &pass_json)])?),
+				VerifyMode::No,
+			)
+		};
+
+		let pass = read(pass_json(|json| json["barcode"] = barcode.clone())?)?;
+		assert_eq!(pass.deprecations(), [Deprecation::Barcode]);
+		assert_eq!(pass.metadata.barcodes.len(), 1);
+		assert_eq!(pass.metadata.barcodes[0].message, "1234");
+
+		// the barcodes array takes precedence
+		let pass = read(pass_json(|json| {
+			json["barcode"] = barcode.clone();
+			let mut other = barcode.clone();
+			other["message"] = "5678".into();
+			json["barcodes"] = serde_json::json!([other]);
+		})?)?;
+		assert_eq!(pass.deprecations(), [Deprecation::Barcode]);
+		assert_eq!(pass.metadata.barcodes[0].message, "5678");
+
+		assert!(round_trip(&pass)?.deprecations().is_empty());
+
+		Ok(())
+	}
+
+	#[test]
+	fn read_json_with_bom() -> Result<()> {
+		let bom = |json: Vec<u8>| [b"\xEF\xBB\xBF".to_vec(), json].concat();
+
+		let pass_json = bom(pass_json(|_| {})?);
+		let pass = Pass::read(
+			Cursor::new(archive(&[("pass.json", &pass_json)])?),
+			VerifyMode::No,
+		)?;
+		assert_eq!(pass.metadata.serial_number(), "1234");
+
+		let manifest = bom(manifest_json(&[("pass.json", &pass_json)])?);
+		let archive = zip_archive(&[("pass.json", &pass_json), ("manifest.json", &manifest)])?;
+		assert!(Pass::read(Cursor::new(archive), VerifyMode::No).is_ok());
+
+		Ok(())
+	}
+
+	#[test]
+	fn read_metadata_only() -> Result<()> {
+		let mut pass = Pass::new(config());
+		pass.metadata.logo_text = Some("Acme".into());
+		pass.assets.images.icon.size_x1 = Some(b"icon".to_vec());
+		let mut written = Cursor::new(vec![]);
+		pass.write(&identity(), &mut written)?;
+
+		let full = Pass::read(Cursor::new(written.get_ref()), VerifyMode::No)?;
+		let metadata = Pass::read_metadata_only(Cursor::new(written.get_ref()), VerifyMode::No)?;
+		assert_eq!(metadata.to_pretty_json()?, full.metadata.to_pretty_json()?);
+
+		assert!(matches!(
+			Pass::read_metadata_only(Cursor::new(archive(&[])?), VerifyMode::No),
+			Err(Error::MissingFile("pass.json"))
+		));
+
+		Ok(())
+	}
+
+	#[test]
+	#[cfg(feature = "sign")]
+	fn read_metadata_only_verified() -> Result<()> {
+		let (identity, store) = Identity::test_self_signed()?;
+		let verify = VerifyMode::Custom(Arc::new(store));
+
+		let mut pass = Pass::new(config());
+		pass.assets.images.icon.size_x1 = Some(b"icon".to_vec());
+		let mut signed = Cursor::new(vec![]);
+		pass.write(&identity, &mut signed)?;
+		let signed = signed.into_inner();
+
+		let metadata = Pass::read_metadata_only(Cursor::new(&signed), verify.clone())?;
+		assert_eq!(metadata.serial_number(), "1234");
+
+		// assets are never read, so one not matching the manifest goes unnoticed
+		let asset = replace_entry(&signed, "icon.png", b"not the icon")?;
+		let metadata = Pass::read_metadata_only(Cursor::new(&asset), verify.clone())?;
+		assert_eq!(metadata.serial_number(), "1234");
+		assert!(matches!(
+			Pass::read(Cursor::new(&asset), verify.clone()),
+			Err(Error::ManifestSignatureMismatch(file)) if file == "icon.png"
+		));
+
+		// pass.json edited after the manifest was signed
+		let edited = pass_json(|json| json["serialNumber"] = "5678".into())?;
+		let edited = replace_entry(&signed, "pass.json", &edited)?;
+		assert!(Pass::read_metadata_only(Cursor::new(&edited), VerifyMode::No).is_ok());
+		assert!(matches!(
+			Pass::read_metadata_only(Cursor::new(&edited), verify.clone()),
+			Err(Error::ManifestSignatureMismatch(file)) if file == "pass.json"
+		));
+
+		// manifest edited after it was signed, to vouch for another icon
+		let mut zip = ZipArchive::new(Cursor::new(&signed))?;
+		let mut manifest = vec![];
+		zip.by_name("manifest.json")?.read_to_end(&mut manifest)?;
+		let mut edited: serde_json::Value = serde_json::from_slice(&manifest)?;
+		edited["icon.png"] = ManifestHashPolicy::Sha1.digest(b"not the icon").into();
+		let edited = replace_entry(&asset, "manifest.json", &serde_json::to_vec(&edited)?)?;
+		assert!(matches!(
+			Pass::read_metadata_only(Cursor::new(&edited), verify.clone()),
+			Err(Error::OpenSsl(_))
+		));
+
+		// same manifest, signed by an identity the store doesn't trust
+		let (other, _) = Identity::test_self_signed()?;
+		let signature = other.signer().expect("identity signs").sign(&manifest)?;
+		let edited = replace_entry(&signed, "signature", &signature)?;
+		assert!(matches!(
+			Pass::read_metadata_only(Cursor::new(&edited), verify),
+			Err(Error::OpenSsl(_))
+		));
+
+		Ok(())
+	}
+
+	#[test]
+	fn write_unsigned_directory() -> Result<()> {
+		let mut pass = Pass::new(config());
+		pass.metadata.pass_type_identifier = "pass.com.acme".into();
+		pass.metadata.team_identifier = "ACME123456".into();
+		pass.assets.images.icon.size_x1 = Some(b"icon".to_vec());
+		pass.assets
+			.get_by_tag("fr")?
+			.insert_string("title", "Titre");
+
+		let dir = std::env::temp_dir().join(format!("pkpass-unsigned-{}", std::process::id()));
+		let result = (|| -> Result<_> {
+			pass.write_assets_to_dir(&dir)?;
+			pass.write_pass_json(fs::File::create(dir.join("pass.json"))?)?;
+			pass.write_manifest(
+				fs::File::create(dir.join("manifest.json"))?,
+				ManifestHashPolicy::default(),
+			)?;
+
+			let mut entries = vec![];
+			for name in [
+				"pass.json",
+				"manifest.json",
+				"icon.png",
+				"fr.lproj/pass.strings",
+			] {
+				entries.push((name, fs::read(dir.join(name))?));
+			}
+			Ok(entries)
+		})();
+		let _ = fs::remove_dir_all(&dir);
+		let entries = result?;
+
+		// the same files as the archive written by `write`
+		let mut archive = Cursor::new(vec![]);
+		pass.write(&identity(), &mut archive)?;
+		let mut zip = ZipArchive::new(archive)?;
+		for (name, content) in &entries {
+			let mut written = vec![];
+			zip.by_name(name)?.read_to_end(&mut written)?;
+			if *name == "manifest.json" {
+				// digests are kept in a hash map, compare them regardless of order
+				assert_eq!(
+					serde_json::from_slice::<serde_json::Value>(content)?,
+					serde_json::from_slice::<serde_json::Value>(&written)?
+				);
+			} else {
+				assert_eq!(content, &written, "{name}");
+			}
+		}
+
+		Ok(())
+	}
+
+	#[test]
+	fn flags_omitted_when_default() -> Result<()> {
+		let mut pass = Pass::new(config());
+		let keys = |pass: &Pass| -> Result<[bool; 2]> {
+			let pass_json = serde_json::to_value(&pass.metadata)?;
+			Ok(["suppressStripShine", "sharingProhibited"].map(|key| pass_json.get(key).is_some()))
+		};
+
+		pass.suppress_strip_shine(true);
+		pass.sharing_prohibited(false);
+		assert_eq!(keys(&pass)?, [false, false]);
+
+		pass.suppress_strip_shine(false);
+		pass.sharing_prohibited(true);
+		assert_eq!(keys(&pass)?, [true, true]);
+		assert_eq!(pass.metadata.suppress_strip_shine, Some(false));
+		assert_eq!(pass.metadata.sharing_prohibited, Some(true));
+
+		Ok(())
+	}
+
+	#[test]
+	fn read_wrapped_archives() -> Result<()> {
+		let archive = archive(&[("pass.json", &pass_json(|_| {})?)])?;
+
+		let mut gzip = flate2::write::GzEncoder::new(vec![], flate2::Compression::default());
+		gzip.write_all(&archive)?;
+		let gzip = gzip.finish()?;
+
+		let mut base64 = STANDARD.encode(&gzip);
+		base64.insert(20, '\n');
+
+		for input in [&archive, &gzip, base64.as_bytes()] {
+			let pass = Pass::read_auto(input, VerifyMode::No)?;
+			assert_eq!(pass.metadata.serial_number(), "1234");
+		}
+
+		// the strict reader stays strict
+		assert!(Pass::read(Cursor::new(&gzip), VerifyMode::No).is_err());
+
+		// gzip layers are inflated up to a limit
+		let len = archive.len() as u64;
+		assert_eq!(gunzip(&gzip, len)?, archive);
+		assert!(matches!(
+			gunzip(&gzip, len - 1),
+			Err(Error::PassTooLarge(limit)) if limit == len - 1
+		));
+		assert!(matches!(
+			Pass::read_auto(b"<html>Not Found</html>", VerifyMode::No),
+			Err(Error::UnknownArchiveFormat)
+		));
+
+		Ok(())
+	}
+
+	#[test]
+	fn read_special_files_of_any_case() -> Result<()> {
+		let pass_json = pass_json(|_| {})?;
+		let manifest = manifest_json(&[("pass.json", &pass_json), ("icon.png", b"icon")])?;
+		let archive = zip_archive(&[
+			("pass.JSON", &pass_json),
+			("icon.png", b"icon"),
+			("Manifest.json", &manifest),
+		])?;
+
+		let pass = Pass::read(Cursor::new(&archive), VerifyMode::No)?;
+		assert_eq!(pass.metadata.serial_number(), "1234");
+		assert_eq!(
+			pass.assets.images.icon.size_x1.as_deref(),
+			Some(&b"icon"[..])
+		);
+		let metadata = Pass::read_metadata_only(Cursor::new(&archive), VerifyMode::No)?;
+		assert_eq!(metadata.serial_number(), "1234");
+
+		// written back with the exact names
+		let mut written = Cursor::new(vec![]);
+		pass.write(&identity(), &mut written)?;
+		let zip = ZipArchive::new(written)?;
+		let mut names = zip.file_names().collect::<Vec<_>>();
+		names.sort_unstable();
+		assert_eq!(names, ["icon.png", "manifest.json", "pass.json"]);
+
+		Ok(())
+	}
+
+	#[test]
+	fn write_sha256_manifest() -> Result<()> {
+		let mut pass = Pass::new(config());
+		pass.assets.images.icon.size_x1 = Some(b"icon".to_vec());
+
+		let mut archive = Cursor::new(vec![]);
+		let options = WriteOptions::default().manifest_hash(ManifestHashPolicy::Sha256);
+		pass.write_with(&identity(), &mut archive, options)?;
+
+		let mut zip = ZipArchive::new(Cursor::new(archive.get_ref()))?;
+		let mut manifest = vec![];
+		zip.by_name("manifest.json")?.read_to_end(&mut manifest)?;
+		let manifest: serde_json::Value = serde_json::from_slice(&manifest)?;
+		assert_eq!(manifest["icon.png"], hex::encode(sha256(b"icon")));
+
+		let read = Pass::read(archive, VerifyMode::No)?;
+		assert_eq!(read.assets, pass.assets);
+
+		Ok(())
+	}
+
+	#[test]
+	fn read_rejects_path_traversal() -> Result<()> {
+		let pass_json = pass_json(|_| {})?;
+		let archive = zip_archive(&[
+			("pass.json", &pass_json),
+			(
+				"manifest.json",
+				&manifest_json(&[("pass.json", &pass_json)])?,
+			),
+			("fr.lproj/../../../etc/passwd", b"root"),
+		])?;
+
+		assert!(matches!(
+			Pass::read(Cursor::new(archive), VerifyMode::No),
+			Err(Error::UnsafeAssetPath(path)) if path == "fr.lproj/../../../etc/passwd"
+		));
+
+		for path in [
+			"../custom.bin",
+			"/etc/passwd",
+			"\\etc\\passwd",
+			"C:\\Windows",
+			"a/../b",
+		] {
+			assert!(
+				matches!(check_asset_path(path), Err(Error::UnsafeAssetPath(_))),
+				"{path}"
+			);
+		}
+		for path in [
+			"custom.bin",
+			"fr.lproj/logo.png",
+			"..custom",
+			"vendor/..bin",
+		] {
+			assert!(check_asset_path(path).is_ok(), "{path}");
+		}
+		let mut pass = Pass::new(config());
+		assert!(pass
+			.insert_raw_asset("../custom.bin".into(), vec![])
+			.is_err());
+
+		Ok(())
+	}
+
+	#[test]
+	fn read_unknown_assets() -> Result<()> {
+		let archive = archive(&[
+			("pass.json", &pass_json(|_| {})?),
+			("icon.png", b"icon"),
+			("audio.caf", b"audio"),
+		])?;
+		assert!(matches!(
+			Pass::read(Cursor::new(&archive), VerifyMode::No),
+			Err(Error::Io(_))
+		));
+
+		let options = ReadOptions::default()
+			.verify(VerifyMode::No)
+			.keep_unknown_assets(true);
+		let pass = Pass::read_with(Cursor::new(&archive), options.clone())?;
+		assert_eq!(
+			pass.assets.images.icon.size_x1.as_deref(),
+			Some(&b"icon"[..])
+		);
+		assert_eq!(
+			pass.assets.raw().get("audio.caf").map(Vec::as_slice),
+			Some(&b"audio"[..])
+		);
+
+		let mut written = Cursor::new(vec![]);
+		pass.write(&identity(), &mut written)?;
+		written.set_position(0);
+		assert_eq!(Pass::read_with(written, options)?.assets, pass.assets);
+
+		Ok(())
+	}
+
+	#[test]
+	fn write_raw_asset() -> Result<()> {
+		let mut pass = Pass::new(config());
+		pass.insert_raw_asset("custom.bin".into(), vec![0, 1, 2])?;
+		pass.insert_raw_asset("icon.png".into(), b"icon".to_vec())?;
+		assert_eq!(
+			pass.assets.images.icon.size_x1.as_deref(),
+			Some(&b"icon"[..])
+		);
+		assert_eq!(pass.assets.raw().keys().collect::<Vec<_>>(), ["custom.bin"]);
+		assert!(matches!(
+			pass.insert_raw_asset("Manifest.json".into(), vec![]),
+			Err(Error::ReservedAssetPath(path)) if path == "Manifest.json"
+		));
+
+		let mut archive = Cursor::new(vec![]);
+		pass.write(&identity(), &mut archive)?;
+
+		let mut zip = ZipArchive::new(archive)?;
+		let mut custom = vec![];
+		zip.by_name("custom.bin")?.read_to_end(&mut custom)?;
+		assert_eq!(custom, [0, 1, 2]);
+		let mut manifest = vec![];
+		zip.by_name("manifest.json")?.read_to_end(&mut manifest)?;
+		let manifest: Manifest = serde_json::from_slice(&manifest)?;
+		assert!(manifest.verify_file("custom.bin", &custom));
+
+		Ok(())
+	}
+
+	#[test]
+	fn write_within_size_budget() -> Result<()> {
+		let mut pass = Pass::new(config());
+		pass.assets.images.icon.size_x1 = Some(vec![0; 64 * 1024]);
+
+		let mut archive = Cursor::new(vec![]);
+		let size = pass.write_with(&identity(), &mut archive, WriteOptions::default())?;
+		assert_eq!(size, archive.get_ref().len() as u64);
+
+		let mut archive = Cursor::new(vec![]);
+		let options = WriteOptions::default().max_size(size);
+		assert_eq!(pass.write_with(&identity(), &mut archive, options)?, size);
+
+		let mut archive = Cursor::new(vec![]);
+		let options = WriteOptions::default().max_size(16 * 1024);
+		assert!(matches!(
+			pass.write_with(&identity(), &mut archive, options),
+			Err(Error::PassTooLarge(16384))
+		));
+		assert!(archive.get_ref().len() <= 16 * 1024);
+
+		Ok(())
+	}
+
+	#[test]
+	fn write_manifest_matches_archive() -> Result<()> {
+		let mut pass = Pass::new(config());
+		pass.metadata.pass_type_identifier = "pass.com.acme".into();
+		pass.metadata.team_identifier = "ACME123456".into();
+		pass.assets.images.icon.size_x1 = Some(b"icon".to_vec());
+
+		for hash in [ManifestHashPolicy::Sha1, ManifestHashPolicy::Sha256] {
+			let mut manifest = vec![];
+			pass.write_manifest(&mut manifest, hash)?;
+
+			let mut archive = Cursor::new(vec![]);
+			let options = WriteOptions::default().manifest_hash(hash);
+			pass.write_with(&identity(), &mut archive, options)?;
+			let mut written = vec![];
+			ZipArchive::new(archive)?
+				.by_name("manifest.json")?
+				.read_to_end(&mut written)?;
+
+			assert_eq!(
+				serde_json::from_slice::<serde_json::Value>(&manifest)?,
+				serde_json::from_slice::<serde_json::Value>(&written)?,
+				"{hash:?}"
+			);
+		}
+
+		Ok(())
+	}
+
+	#[test]
+	fn read_backslash_separators() -> Result<()> {
+		let pass_json = pass_json(|_| {})?;
+		let strings = b"\"title\" = \"Titre\";\n";
+
+		// the manifest may spell the path with either separator
+		for listed in ["fr.lproj/pass.strings", "fr.lproj\\pass.strings"] {
+			let manifest = manifest_json(&[("pass.json", &pass_json), (listed, strings)])?;
+			let archive = zip_archive(&[
+				("pass.json", &pass_json),
+				("fr.lproj\\pass.strings", strings),
+				("manifest.json", &manifest),
+			])?;
+
+			let pass = Pass::read(Cursor::new(archive), VerifyMode::No)?;
+			let fr = pass.assets.localized(&Assets::parse_language("fr")?);
+			assert_eq!(fr.and_then(|fr| fr.strings.as_deref()), Some(&strings[..]));
+		}
+
+		Ok(())
+	}
+
+	#[test]
+	fn read_unknown_pass_key() -> Result<()> {
+		let future = pass_json(|json| json["futureKey"] = true.into())?;
+		let result = Pass::read(
+			Cursor::new(archive(&[("pass.json", &future)])?),
+			VerifyMode::No,
+		);
+		assert!(matches!(result, Err(Error::UnknownPassKey(key)) if key == "futureKey"));
+
+		// other errors are left as they are
+		let malformed = pass_json(|json| json["serialNumber"] = 1234.into())?;
+		let result = Pass::read(
+			Cursor::new(archive(&[("pass.json", &malformed)])?),
+			VerifyMode::No,
+		);
+		assert!(matches!(result, Err(Error::Json(_))));
+
+		// unknown keys of a field are not unknown keys of the pass
+		let nested = pass_json(|json| {
+			json["eventTicket"] = serde_json::json!({
+				"primaryFields": [{ "key": "event", "value": "Concert", "futureKey": true }],
+			});
+			json.as_object_mut().map(|json| json.remove("generic"));
+		})?;
+		let result = Pass::read(
+			Cursor::new(archive(&[("pass.json", &nested)])?),
+			VerifyMode::No,
+		);
+		assert!(matches!(result, Err(Error::Json(err)) if err.to_string().contains("futureKey")));
+
+		Ok(())
+	}
+
+	#[test]
+	fn metadata_keys_are_pass_json_keys() -> Result<()> {
+		let keys = metadata_keys();
+		assert!(keys.contains(&"serialNumber"));
+		assert!(keys.contains(&"eventTicket"));
+		assert!(!keys.contains(&"primaryFields"));
+
+		let json = serde_json::to_value(Pass::new(config()).metadata)?;
+		let json = json.as_object().expect("metadata is an object");
+		assert!(json.keys().all(|key| keys.contains(&key.as_str())));
+
+		Ok(())
+	}
+
+	#[test]
+	fn set_dates() -> Result<()> {
+		let mut pass = Pass::new(config());
+		let date = Utc.with_ymd_and_hms(2024, 7, 22, 16, 30, 0).unwrap();
+		pass.set_relevant_date(date + chrono::Duration::milliseconds(250));
+		pass.set_expiration_date(
+			DateTime::parse_from_rfc3339("2024-07-23T02:00:00+02:00")
+				.expect("valid timestamp")
+				.with_timezone(&Utc),
+		);
+
+		let pass_json = serde_json::to_value(&pass.metadata)?;
+		assert_eq!(pass_json["relevantDate"], "2024-07-22T16:30:00Z");
+		assert_eq!(pass_json["expirationDate"], "2024-07-23T00:00:00Z");
+		assert_eq!(pass.metadata.relevant_date, Some(date));
+
+		Ok(())
+	}
+
+	#[test]
+	#[cfg(feature = "sign")]
+	fn verify_with_custom_store() -> Result<()> {
+		let (identity, store) = Identity::test_self_signed()?;
+		let verify = VerifyMode::Custom(Arc::new(store));
+
+		let mut archive = Cursor::new(vec![]);
+		Pass::new(config()).write(&identity, &mut archive)?;
+
+		let pass = Pass::read(Cursor::new(archive.get_ref()), verify.clone())?;
+		assert_eq!(pass.metadata.pass_type_identifier, "pass.com.example.test");
+		let metadata = Pass::read_metadata_only(Cursor::new(archive.get_ref()), verify.clone())?;
+		assert_eq!(metadata.team_identifier(), "TEST123456");
+
+		// signed by an identity the store doesn't trust
+		let (_, other) = Identity::test_self_signed()?;
+		assert!(matches!(
+			Pass::read(archive, VerifyMode::Custom(Arc::new(other))),
+			Err(Error::OpenSsl(_))
+		));
+
+		// not signed at all
+		let mut unsigned = Cursor::new(vec![]);
+		Pass::new(config()).write(&testing::identity(), &mut unsigned)?;
+		let unsigned = unsigned.into_inner();
+		assert!(matches!(
+			Pass::read(Cursor::new(&unsigned), verify.clone()),
+			Err(Error::MissingFile("signature"))
+		));
+		assert!(matches!(
+			Pass::read_metadata_only(Cursor::new(&unsigned), verify),
+			Err(Error::MissingFile("signature"))
+		));
+
+		Ok(())
+	}
+
+	#[test]
+	#[cfg(feature = "sign")]
+	fn write_with_external_signer() -> Result<()> {
+		use std::sync::atomic::{AtomicUsize, Ordering};
+
+		/// Stands for a key held in an HSM, only reachable through `sign`
+		#[derive(Debug)]
+		struct Token {
+			pen: SigningPen,
+			signatures: Arc<AtomicUsize>,
+		}
+
+		impl Signer for Token {
+			fn sign(&self, manifest: &[u8]) -> Result<Vec<u8>> {
+				self.signatures.fetch_add(1, Ordering::Relaxed);
+				self.pen.sign(manifest)
+			}
+		}
+
+		let (identity, store) = Identity::test_self_signed()?;
+		let signatures = Arc::new(AtomicUsize::new(0));
+		let token = Token {
+			pen: identity.pen.expect("test identity signs"),
+			signatures: Arc::clone(&signatures),
+		};
+		let identity = Identity::with_signer(identity.pass_type_id, identity.team_id, token);
+
+		let mut archive = Cursor::new(vec![]);
+		Pass::new(config()).write(&identity, &mut archive)?;
+		assert_eq!(signatures.load(Ordering::Relaxed), 1);
+
+		let pass = Pass::read(archive, VerifyMode::Custom(Arc::new(store)))?;
+		assert!(pass.signer.is_some());
+
+		Ok(())
+	}
+
+	#[test]
+	#[cfg(feature = "sign")]
+	fn verify_pinned_signer() -> Result<()> {
+		let (identity, _) = Identity::test_self_signed()?;
+		let pen = identity.pen.as_ref().expect("test identity signs");
+		let fingerprint = VerifyMode::fingerprint(&pen.signer_certificate)?;
+
+		let mut archive = Cursor::new(vec![]);
+		Pass::new(config()).write(&identity, &mut archive)?;
+
+		let pinned = VerifyMode::PinnedSigner(vec![[0; 32], fingerprint]);
+		let pass = Pass::read(Cursor::new(archive.get_ref()), pinned)?;
+		assert_eq!(pass.metadata.team_identifier, "TEST123456");
+
+		let (other, _) = Identity::test_self_signed()?;
+		let other =
+			VerifyMode::fingerprint(&other.pen.expect("test identity signs").signer_certificate)?;
+		assert!(matches!(
+			Pass::read(
+				Cursor::new(archive.get_ref()),
+				VerifyMode::PinnedSigner(vec![other])
+			),
+			Err(Error::SignerNotPinned)
+		));
+
+		// an unsigned pass has no signer to match
+		let mut unsigned = Cursor::new(vec![]);
+		Pass::new(config()).write(&testing::identity(), &mut unsigned)?;
+		assert!(matches!(
+			Pass::read(unsigned, VerifyMode::PinnedSigner(vec![fingerprint])),
+			Err(Error::MissingFile("signature"))
+		));
+
+		Ok(())
+	}
+
+	#[test]
+	#[cfg(feature = "apple")]
+	fn verify_rejects_stripped_signature() -> Result<()> {
+		let (identity, store) = Identity::test_self_signed()?;
+		let pen = identity.pen.as_ref().expect("test identity signs");
+		let fingerprint = VerifyMode::fingerprint(&pen.signer_certificate)?;
+		let mut signed = Cursor::new(vec![]);
+		Pass::new(config()).write(&identity, &mut signed)?;
+
+		// without a signature, pass.json and the manifest could be rewritten at will
+		let mut input = ZipArchive::new(Cursor::new(signed.get_ref()))?;
+		let mut stripped = zip::ZipWriter::new(Cursor::new(vec![]));
+		for index in 0..input.len() {
+			let file = input.by_index(index)?;
+			if file.name() != "signature" {
+				stripped.raw_copy_file(file)?;
+			}
+		}
+		let stripped = stripped.finish()?.into_inner();
+		assert!(Pass::read(Cursor::new(&stripped), VerifyMode::No).is_ok());
+
+		for verify in [
+			VerifyMode::Yes,
+			VerifyMode::Custom(Arc::new(store)),
+			VerifyMode::PinnedSigner(vec![fingerprint]),
+		] {
+			assert!(matches!(
+				Pass::read(Cursor::new(&stripped), verify.clone()),
+				Err(Error::MissingFile("signature"))
+			));
+			assert!(matches!(
+				Pass::read_metadata_only(Cursor::new(&stripped), verify),
+				Err(Error::MissingFile("signature"))
+			));
+		}
+
+		Ok(())
+	}
+
+	#[test]
+	fn round_trip_poster_event_ticket() -> Result<()> {
+		// the keys and images of the poster layout introduced in iOS 18
+		let mut pass_json = minimal_pass_json();
+		pass_json["preferredStyleSchemes"] =
+			serde_json::json!(["posterEventTicket", "eventTicket"]);
+		pass_json["eventTicket"] = serde_json::json!({
+			"primaryFields": [{ "key": "event", "value": "The Acme Band" }],
+		});
+		pass_json["semantics"] = serde_json::json!({
+			"eventName": "The Acme Band",
+			"venueName": "Acme Arena",
+		});
+		let archive = archive(&[
+			("pass.json", &serde_json::to_vec(&pass_json)?),
+			("artwork@2x.png", b"artwork"),
+			("logo.png", b"logo"),
+		])?;
+
+		let pass = Pass::read(Cursor::new(archive), VerifyMode::No)?;
+		assert_eq!(
+			pass.metadata.preferred_style_schemes,
+			[StyleScheme::PosterEventTicket, StyleScheme::EventTicket]
+		);
+		assert_eq!(
+			pass.assets.images.artwork.size_x2.as_deref(),
+			Some(&b"artwork"[..])
+		);
+
+		let read = round_trip(&pass)?;
+		assert_eq!(
+			serde_json::to_value(&read.metadata)?["preferredStyleSchemes"],
+			pass_json["preferredStyleSchemes"]
+		);
+		assert_eq!(read.assets, pass.assets);
+
+		Ok(())
+	}
+
+	#[test]
+	#[cfg(feature = "sign")]
+	fn resign_edited_pass() -> Result<()> {
+		let (first, _) = testing::self_signed("pass.com.acme", "ACME123456")?;
+		let (second, cert) = testing::self_signed("pass.com.acme", "ACME123456")?;
+
+		let mut pass = Pass::new(PassConfig {
+			kind: PassKind::StoreCard(Fields {
+				primary: vec![Field::new("balance", "10 €")],
+				..Fields::default()
+			}),
+			..config()
+		});
+		let mut archive = Cursor::new(vec![]);
+		pass.write(&first, &mut archive)?;
+
+		archive.set_position(0);
+		pass = Pass::read(archive, VerifyMode::No)?;
+		let balance = pass.metadata.field_mut("balance").expect("field exists");
+		balance.value = "5 €".into();
+
+		let mut archive = Cursor::new(vec![]);
+		pass.resign(&second, &mut archive)?;
+		verify_signature(archive.get_ref(), &cert)?;
+
+		archive.set_position(0);
+		let mut pass = Pass::read(archive, VerifyMode::No)?;
+		assert_eq!(pass.signer().map(X509Ref::to_owned), Some(cert));
+		assert_eq!(
+			pass.metadata
+				.field_mut("balance")
+				.map(|field| &field.value[..]),
+			Some("5 €")
+		);
+
+		Ok(())
+	}
+
+	#[test]
+	fn read_without_manifest_digests() -> Result<()> {
+		// the manifest lists the untouched files
+		let archive = |json: Vec<u8>, icon: &[u8]| -> Result<Vec<u8>> {
+			let manifest = manifest_json(&[
+				("pass.json", &pass_json(|_| {})?),
+				("icon.png", b"expected icon"),
+			])?;
+			zip_archive(&[
+				("pass.json", &json),
+				("icon.png", icon),
+				("manifest.json", &manifest),
+			])
+		};
+		let options = ReadOptions::default()
+			.verify(VerifyMode::No)
+			.verify_manifest_digests(false);
+
+		let tampered_icon = archive(pass_json(|_| {})?, b"tampered icon")?;
+		assert!(matches!(
+			Pass::read(Cursor::new(&tampered_icon), VerifyMode::No),
+			Err(Error::ManifestSignatureMismatch(file)) if file == "icon.png"
+		));
+		let pass = Pass::read_with(Cursor::new(&tampered_icon), options.clone())?;
+		assert_eq!(pass.metadata.serial_number, "1234");
+		assert_eq!(
+			pass.assets.images.icon.size_x1.as_deref(),
+			Some(&b"tampered icon"[..])
+		);
+
+		let tampered_pass_json = archive(
+			pass_json(|pass| pass["serialNumber"] = "5678".into())?,
+			b"expected icon",
+		)?;
+		assert!(matches!(
+			Pass::read(Cursor::new(&tampered_pass_json), VerifyMode::No),
+			Err(Error::ManifestSignatureMismatch(file)) if file == "pass.json"
+		));
+		let pass = Pass::read_with(Cursor::new(&tampered_pass_json), options)?;
+		assert_eq!(pass.metadata.serial_number, "5678");
+
+		Ok(())
+	}
+
+	#[test]
+	fn read_never_panics() -> Result<()> {
+		let mut pass = Pass::new(config());
+		pass.assets.images.icon.size_x1 = Some(vec![0; 64]);
+		pass.assets
+			.get("fr".parse().expect("valid language"))
+			.strings = Some(vec![]);
+		let mut valid = Cursor::new(vec![]);
+		pass.write(&identity(), &mut valid)?;
+		let valid = valid.into_inner();
+
+		// xorshift, deterministic and good enough to shake the reader
+		let mut state = 0x2545_f491_4f6c_dd1d_u64;
+		let mut random = move || {
+			state ^= state << 13;
+			state ^= state >> 7;
+			state ^= state << 17;
+			state
+		};
+
+		let mut inputs = vec![];
+		for _ in 0..256 {
+			let len = random() % 512;
+			inputs.push((0..len).map(|_| random().to_le_bytes()[0]).collect());
+		}
+		for len in 0..valid.len() {
+			inputs.push(valid[..len].to_vec());
+		}
+		for _ in 0..1024 {
+			let mut mutated = valid.clone();
+			let position = usize::try_from(random()).unwrap_or_default() % mutated.len();
+			mutated[position] = random().to_le_bytes()[0];
+			inputs.push(mutated);
+		}
+		inputs.push(archive(&[("pass.json", b"{")])?);
+		inputs.push(archive(&[
+			("pass.json", &pass_json(|_| ())?),
+			("not a language.lproj/pass.strings", b""),
+		])?);
+		inputs.push(archive(&[
+			("pass.json", &pass_json(|_| ())?),
+			("sound.caf", b""),
+		])?);
+
+		for input in inputs {
+			let _ = Pass::read(Cursor::new(input), VerifyMode::No);
+		}
+
+		let missing = Pass::read(Cursor::new(archive(&[])?), VerifyMode::No);
+		assert!(matches!(missing, Err(Error::MissingFile("pass.json"))));
+
+		Ok(())
+	}
+
+	#[test]
+	fn write_to_unseekable_writer() -> Result<()> {
+		/// Only implements `Write`, like a pipe
+		struct Pipe(Vec<u8>);
+
+		impl Write for Pipe {
+			fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+				self.0.write(buf)
+			}
+
+			fn flush(&mut self) -> std::io::Result<()> {
+				Ok(())
+			}
+		}
+
+		let mut pipe = Pipe(vec![]);
+		Pass::new(config()).write_to(&identity(), &mut pipe)?;
+
+		let pass = Pass::read(Cursor::new(pipe.0), VerifyMode::No)?;
+		assert_eq!(pass.metadata.serial_number, "1234");
+
+		Ok(())
+	}
+
+	#[test]
+	#[cfg(feature = "sign")]
+	fn identity_mismatch() -> Result<()> {
+		let (identity, _) = testing::self_signed("pass.com.acme", "ACME123456")?;
+
+		let mut archive = Cursor::new(vec![]);
+		Pass::new(config()).write(&identity, &mut archive)?;
+		archive.set_position(0);
+
+		let mut pass = Pass::read(archive, VerifyMode::No)?;
+		pass.verify_identity_match()?;
+
+		pass.metadata.team_identifier = "OTHER12345".into();
+		let result = pass.verify_identity_match();
+		assert!(matches!(
+			result,
+			Err(Error::IdentityMismatch {
+				field: "teamIdentifier",
+				..
+			})
+		));
+
+		pass.strip_signature();
+		pass.verify_identity_match()?;
+
+		Ok(())
+	}
 }
@@ -0,0 +1,764 @@
+//! Advisory checks on passes
+//!
+//! Lints point out things Wallet accepts but silently ignores or renders
+//! differently than expected. They never prevent writing a pass.
+
+use crate::{
+	models::Assets,
+	models::{semantics, Image, Metadata, Version},
+	Pass,
+};
+use chrono::{DateTime, Utc};
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub enum Lint {
+	/// `associatedStoreIdentifiers` are only honored on payment passes
+	StoreIdentifiersIgnored,
+	/// `appLaunchURL` is set but no associated app can be launched with it
+	AppLaunchUrlWithoutStoreIdentifiers,
+	/// Foreground and background colors are hard to tell apart
+	LowContrast { ratio: f64 },
+	/// A semantic tag expecting an uppercase IATA code holds something else
+	InvalidIataCode { key: &'static str, code: String },
+	/// A currency code is not an active ISO 4217 code, `key` is the field key
+	/// or the semantic tag holding it
+	UnknownCurrencyCode { key: String, code: String },
+	/// Wallet only considers the first 10 locations, the others are ignored
+	TooManyLocations { ignored: Vec<usize> },
+	/// Wallet only considers the first 10 beacons, the others are ignored
+	TooManyBeacons { ignored: Vec<usize> },
+	/// Two beacons share the same proximity UUID, major and minor identifiers
+	DuplicateBeacon { first: usize, duplicate: usize },
+	/// A barcode has no human-readable text to fall back on when it can't be scanned
+	BarcodeWithoutAltText { index: usize },
+	/// A `@2x` or `@3x` image is not exactly a multiple of the standard one,
+	/// only checked with the `image` feature
+	MismatchedImageScale {
+		image: Image,
+		version: Version,
+		expected: (u32, u32),
+		actual: (u32, u32),
+	},
+	/// An image the style of the pass doesn't show, such as a strip on a generic pass
+	/// or a background on an event ticket with a strip
+	ImageNotDisplayed { image: Image, kind: &'static str },
+	/// A field `attributedValue` uses an HTML tag other than `<a>`, which Wallet strips
+	DisallowedHtmlTag { key: String, tag: String },
+	/// A field `changeMessage` lacks the `%@` placeholder replaced with the new value
+	ChangeMessageWithoutPlaceholder { key: String },
+	/// A field has an empty key, Wallet can't tell it apart across updates to notify of changes
+	FieldWithoutKey { value: String },
+	/// The expiration date is already past, Wallet will show the pass as expired
+	Expired { expiration_date: DateTime<Utc> },
+}
+
+impl fmt::Display for Lint {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			Self::StoreIdentifiersIgnored => write!(
+				f,
+				"associated store identifiers only work for payment passes and will be ignored"
+			),
+			Self::AppLaunchUrlWithoutStoreIdentifiers => write!(
+				f,
+				"app launch url is set without associated store identifiers, no app will be launched"
+			),
+			Self::LowContrast { ratio } => write!(
+				f,
+				"foreground and background colors have a low contrast ratio of {ratio:.2}:1"
+			),
+			Self::InvalidIataCode { key, code } => {
+				write!(f, "semantic tag `{key}` is not a valid IATA code: `{code}`")
+			}
+			Self::UnknownCurrencyCode { key, code } => {
+				write!(f, "`{key}` uses unknown ISO 4217 currency code `{code}`")
+			}
+			Self::TooManyLocations { ignored } => write!(
+				f,
+				"only the first {MAX_RELEVANT} locations are used, locations {ignored:?} will be ignored"
+			),
+			Self::TooManyBeacons { ignored } => write!(
+				f,
+				"only the first {MAX_RELEVANT} beacons are used, beacons {ignored:?} will be ignored"
+			),
+			Self::DuplicateBeacon { first, duplicate } => {
+				write!(f, "beacon {duplicate} is a duplicate of beacon {first}")
+			}
+			Self::BarcodeWithoutAltText { index } => write!(
+				f,
+				"barcode {index} has no alt text to display when it can't be scanned"
+			),
+			Self::MismatchedImageScale {
+				image,
+				version,
+				expected: (expected_width, expected_height),
+				actual: (width, height),
+			} => write!(
+				f,
+				"{}{version}.png is {width}x{height}, expected {expected_width}x{expected_height}",
+				image.name()
+			),
+			Self::ImageNotDisplayed {
+				image: image @ (Image::Background | Image::Thumbnail),
+				kind: "eventTicket",
+			} => write!(
+				f,
+				"{} images are not displayed on eventTicket passes with a strip image",
+				image.name()
+			),
+			Self::ImageNotDisplayed { image, kind } => write!(
+				f,
+				"{} images are not displayed on {kind} passes",
+				image.name()
+			),
+			Self::DisallowedHtmlTag { key, tag } => write!(
+				f,
+				"attributed value of field `{key}` uses `<{tag}>`, only `<a href>` links are supported"
+			),
+			Self::ChangeMessageWithoutPlaceholder { key } => write!(
+				f,
+				"change message of field `{key}` has no `%@` placeholder for the new value"
+			),
+			Self::FieldWithoutKey { value } => write!(
+				f,
+				"field with value `{value}` has no key, changes to it can't be notified"
+			),
+			Self::Expired { expiration_date } => write!(
+				f,
+				"pass expired on {expiration_date}, Wallet will show it as expired"
+			),
+		}
+	}
+}
+
+/// A deprecated key of `pass.json`, still honored when reading, see [`Pass::deprecations`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Deprecation {
+	/// The single `barcode` of iOS 8 and earlier, replaced by `barcodes`
+	Barcode,
+}
+
+impl fmt::Display for Deprecation {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			Self::Barcode => write!(
+				f,
+				"`barcode` is deprecated since iOS 9, use `barcodes` instead"
+			),
+		}
+	}
+}
+
+/// Tune the checks run by [`Pass::validate`]
+#[derive(Debug, Clone)]
+pub struct Validator {
+	min_contrast: f64,
+	now: Option<DateTime<Utc>>,
+}
+
+impl Default for Validator {
+	fn default() -> Self {
+		Self {
+			min_contrast: 3.,
+			now: None,
+		}
+	}
+}
+
+impl Validator {
+	/// Minimum WCAG contrast ratio between foreground and background colors, defaults to 3:1
+	#[must_use]
+	pub const fn min_contrast(mut self, ratio: f64) -> Self {
+		self.min_contrast = ratio;
+		self
+	}
+
+	/// Date and time to check the expiration date against, defaults to the current one
+	#[must_use]
+	pub const fn now(mut self, now: DateTime<Utc>) -> Self {
+		self.now = Some(now);
+		self
+	}
+}
+
+impl Pass {
+	/// Run advisory checks on the pass with the default [`Validator`]
+	#[must_use]
+	pub fn lint(&self) -> Vec<Lint> {
+		self.validate(&Validator::default())
+	}
+
+	/// Run advisory checks on the pass
+	#[must_use]
+	pub fn validate(&self, validator: &Validator) -> Vec<Lint> {
+		let mut lints = vec![];
+		store_identifiers(&self.metadata, &mut lints);
+		contrast(&self.metadata, validator, &mut lints);
+		iata_codes(&self.metadata, &mut lints);
+		currency_codes(&self.metadata, &mut lints);
+		barcode_alt_texts(&self.metadata, &mut lints);
+		attributed_values(&self.metadata, &mut lints);
+		change_messages(&self.metadata, &mut lints);
+		relevance(&self.metadata, &mut lints);
+		expiration(&self.metadata, validator, &mut lints);
+		displayed_images(&self.metadata, &self.assets, &mut lints);
+		#[cfg(feature = "image")]
+		image_scales(&self.assets, &mut lints);
+		lints
+	}
+}
+
+fn store_identifiers(metadata: &Metadata, lints: &mut Vec<Lint>) {
+	// passes produced by this crate are never payment passes, those are provisioned by card issuers
+	if !metadata.associated_store_identifiers.is_empty() {
+		lints.push(Lint::StoreIdentifiersIgnored);
+	}
+	if metadata.app_launch_url.is_some() && metadata.associated_store_identifiers.is_empty() {
+		lints.push(Lint::AppLaunchUrlWithoutStoreIdentifiers);
+	}
+}
+
+fn contrast(metadata: &Metadata, validator: &Validator, lints: &mut Vec<Lint>) {
+	if let (Some(foreground), Some(background)) =
+		(&metadata.foreground_color, &metadata.background_color)
+	{
+		let ratio = foreground.contrast_ratio(background);
+		if ratio < validator.min_contrast {
+			lints.push(Lint::LowContrast { ratio });
+		}
+	}
+}
+
+fn iata_codes(metadata: &Metadata, lints: &mut Vec<Lint>) {
+	let Some(tags) = &metadata.semantics else {
+		return;
+	};
+
+	let mut check = |key, code: &Option<String>, is_valid: fn(&str) -> bool| {
+		if let Some(code) = code.as_deref().filter(|code| !is_valid(code)) {
+			lints.push(Lint::InvalidIataCode {
+				key,
+				code: code.into(),
+			});
+		}
+	};
+
+	check(
+		"airlineCode",
+		&tags.airline_code,
+		semantics::is_airline_code,
+	);
+	check("flightCode", &tags.flight_code, semantics::is_flight_code);
+	check(
+		"departureAirportCode",
+		&tags.departure_airport_code,
+		semantics::is_airport_code,
+	);
+	check(
+		"destinationAirportCode",
+		&tags.destination_airport_code,
+		semantics::is_airport_code,
+	);
+}
+
+fn currency_codes(metadata: &Metadata, lints: &mut Vec<Lint>) {
+	let fields = metadata
+		.fields()
+		.map(|field| (&field.key[..], &field.currency_code));
+	let tags = metadata.semantics.iter().flat_map(|tags| {
+		[
+			("balance", &tags.balance),
+			("totalPrice", &tags.total_price),
+		]
+		.into_iter()
+		.filter_map(|(key, amount)| Some((key, &amount.as_ref()?.currency_code)))
+	});
+
+	for (key, code) in fields.chain(tags) {
+		if let Some(code) = code
+			.as_deref()
+			.filter(|code| !semantics::is_currency_code(code))
+		{
+			lints.push(Lint::UnknownCurrencyCode {
+				key: key.into(),
+				code: code.into(),
+			});
+		}
+	}
+}
+
+/// Maximum number of locations and of beacons Wallet monitors for a pass
+const MAX_RELEVANT: usize = 10;
+
+fn relevance(metadata: &Metadata, lints: &mut Vec<Lint>) {
+	if metadata.locations.len() > MAX_RELEVANT {
+		lints.push(Lint::TooManyLocations {
+			ignored: (MAX_RELEVANT..metadata.locations.len()).collect(),
+		});
+	}
+	if metadata.beacons.len() > MAX_RELEVANT {
+		lints.push(Lint::TooManyBeacons {
+			ignored: (MAX_RELEVANT..metadata.beacons.len()).collect(),
+		});
+	}
+
+	for (duplicate, beacon) in metadata.beacons.iter().enumerate() {
+		if let Some(first) = metadata.beacons[..duplicate]
+			.iter()
+			.position(|other| other.same_region(beacon))
+		{
+			lints.push(Lint::DuplicateBeacon { first, duplicate });
+		}
+	}
+}
+
+fn barcode_alt_texts(metadata: &Metadata, lints: &mut Vec<Lint>) {
+	for (index, barcode) in metadata.barcodes.iter().enumerate() {
+		if barcode.alt_text.is_none() {
+			lints.push(Lint::BarcodeWithoutAltText { index });
+		}
+	}
+}
+
+fn attributed_values(metadata: &Metadata, lints: &mut Vec<Lint>) {
+	for field in metadata.fields() {
+		let Some(html) = &field.attributed_value else {
+			continue;
+		};
+
+		for tag in html_tags(html) {
+			if !tag.eq_ignore_ascii_case("a") {
+				lints.push(Lint::DisallowedHtmlTag {
+					key: field.key.clone(),
+					tag: tag.into(),
+				});
+			}
+		}
+	}
+}
+
+fn change_messages(metadata: &Metadata, lints: &mut Vec<Lint>) {
+	for field in metadata.fields() {
+		if field.key.trim().is_empty() {
+			lints.push(Lint::FieldWithoutKey {
+				value: field.value.clone(),
+			});
+		}
+		if field
+			.change_message
+			.as_ref()
+			.is_some_and(|message| !message.contains("%@"))
+		{
+			lints.push(Lint::ChangeMessageWithoutPlaceholder {
+				key: field.key.clone(),
+			});
+		}
+	}
+}
+
+/// Names of the opening tags of an HTML fragment, such as `a` for `<a href="…">`
+fn html_tags(html: &str) -> impl Iterator<Item = &str> {
+	html.split('<').skip(1).filter_map(|tag| {
+		let end = tag
+			.find(|c: char| !c.is_ascii_alphanumeric())
+			.unwrap_or(tag.len());
+		// closing tags start with `/` and a lone `<` such as in `1 < 2` is text
+		(end > 0).then(|| &tag[..end])
+	})
+}
+
+fn expiration(metadata: &Metadata, validator: &Validator, lints: &mut Vec<Lint>) {
+	let now = validator.now.unwrap_or_else(Utc::now);
+	if let Some(expiration_date) = metadata.expiration_date.filter(|date| *date < now) {
+		lints.push(Lint::Expired { expiration_date });
+	}
+}
+
+/// Images shown by each pass style, per Apple's pass design guidelines
+///
+/// Every image supports `@2x` and `@3x` versions whatever the style.
+const DISPLAYED_IMAGES: [(&str, &[Image]); 5] = [
+	("boardingPass", &[Image::Icon, Image::Logo, Image::Footer]),
+	("coupon", &[Image::Icon, Image::Logo, Image::Strip]),
+	(
+		"eventTicket",
+		&[
+			Image::Icon,
+			Image::Logo,
+			Image::Strip,
+			Image::Background,
+			Image::Thumbnail,
+			Image::Artwork,
+		],
+	),
+	("generic", &[Image::Icon, Image::Logo, Image::Thumbnail]),
+	("storeCard", &[Image::Icon, Image::Logo, Image::Strip]),
+];
+
+fn displayed_images(metadata: &Metadata, assets: &Assets, lints: &mut Vec<Lint>) {
+	let [kind] = metadata.kinds()[..] else {
+		return;
+	};
+	let Some((_, displayed)) = DISPLAYED_IMAGES.iter().find(|(key, _)| *key == kind) else {
+		return;
+	};
+
+	let is_set = |image| {
+		assets
+			.image_sets()
+			.any(|images| !images.image(image).versions().is_empty())
+	};
+
+	for image in Image::ALL {
+		// event tickets show either a strip, or a background and a thumbnail
+		let hidden_by_strip = kind == "eventTicket"
+			&& matches!(image, Image::Background | Image::Thumbnail)
+			&& is_set(Image::Strip);
+
+		if is_set(image) && (!displayed.contains(&image) || hidden_by_strip) {
+			lints.push(Lint::ImageNotDisplayed { image, kind });
+		}
+	}
+}
+
+#[cfg(feature = "image")]
+fn image_scales(assets: &Assets, lints: &mut Vec<Lint>) {
+	let dimensions = |png: &[u8]| {
+		image::ImageReader::with_format(std::io::Cursor::new(png), image::ImageFormat::Png)
+			.into_dimensions()
+			.ok()
+	};
+
+	for images in assets.image_sets() {
+		for image in Image::ALL {
+			let asset = images.image(image);
+			let Some((width, height)) = asset.size_x1.as_deref().and_then(dimensions) else {
+				continue;
+			};
+
+			for (version, scale, png) in [
+				(Version::Size2X, 2, &asset.size_x2),
+				(Version::Size3X, 3, &asset.size_x3),
+			] {
+				let Some(expected) = scaled((width, height), scale) else {
+					continue;
+				};
+				if let Some(actual) = png.as_deref().and_then(dimensions) {
+					if actual != expected {
+						lints.push(Lint::MismatchedImageScale {
+							image,
+							version,
+							expected,
+							actual,
+						});
+					}
+				}
+			}
+		}
+	}
+}
+
+/// Dimensions of an image `scale` times as large, unless they overflow, in
+/// which case no image can match them
+#[cfg(feature = "image")]
+const fn scaled((width, height): (u32, u32), scale: u32) -> Option<(u32, u32)> {
+	match (width.checked_mul(scale), height.checked_mul(scale)) {
+		(Some(width), Some(height)) => Some((width, height)),
+		_ => None,
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{
+		models::{
+			Barcode, BarcodeFormat, Field, Fields, Location, PassKind, RgbColor,
+			SemanticTagCurrencyAmount, SemanticTags,
+		},
+		testing, PassConfig,
+	};
+	use chrono::TimeZone;
+
+	fn pass() -> Pass {
+		Pass::new(testing::config())
+	}
+
+	#[test]
+	fn lint_store_identifiers() -> Result<(), Box<dyn std::error::Error>> {
+		let mut pass = pass();
+		assert!(pass.lint().is_empty());
+
+		pass.metadata.app_launch_url = Some("acme://open".parse()?);
+		assert_eq!(pass.lint(), [Lint::AppLaunchUrlWithoutStoreIdentifiers]);
+
+		pass.metadata.associated_store_identifiers.push(284_882_215);
+		assert_eq!(pass.lint(), [Lint::StoreIdentifiersIgnored]);
+
+		Ok(())
+	}
+
+	#[test]
+	fn lint_low_contrast() -> Result<(), Box<dyn std::error::Error>> {
+		let pass = pass()
+			.with_foreground_color("rgb(119,119,119)")?
+			.with_background_color("rgb(136,136,136)")?;
+
+		let lints = pass.lint();
+		assert!(
+			matches!(lints[..], [Lint::LowContrast { ratio }] if ratio < 1.5),
+			"{lints:?}"
+		);
+		assert!(pass
+			.validate(&Validator::default().min_contrast(1.))
+			.is_empty());
+
+		Ok(())
+	}
+
+	#[test]
+	fn lint_high_contrast() -> Result<(), Box<dyn std::error::Error>> {
+		let pass = pass()
+			.with_foreground_color(RgbColor::white())?
+			.with_background_color((23, 50, 82))?;
+
+		assert!(pass.lint().is_empty());
+		Ok(())
+	}
+
+	#[test]
+	fn lint_iata_codes() -> Result<(), Box<dyn std::error::Error>> {
+		let mut semantics = SemanticTags::default();
+		semantics.set_airline_code("ex")?;
+		semantics.set_flight_code(" ex123 ")?;
+		semantics.set_departure_airport_code("lhr")?;
+		assert!(semantics.set_destination_airport_code("MAPUTO").is_err());
+
+		assert_eq!(semantics.airline_code.as_deref(), Some("EX"));
+		assert_eq!(semantics.flight_code.as_deref(), Some("EX123"));
+		assert_eq!(semantics.departure_airport_code.as_deref(), Some("LHR"));
+
+		let mut pass = pass();
+		semantics.destination_airport_code = Some("mpm".into());
+		pass.metadata.semantics = Some(semantics);
+
+		assert_eq!(
+			pass.lint(),
+			[Lint::InvalidIataCode {
+				key: "destinationAirportCode",
+				code: "mpm".into()
+			}]
+		);
+
+		Ok(())
+	}
+
+	#[test]
+	fn lint_currency_codes() {
+		let mut pass = pass();
+		let mut price = Field::new("price", "42");
+		price.currency_code = Some("USD".into());
+		let mut fee = Field::new("fee", "2");
+		fee.currency_code = Some("XYZ".into());
+		pass.metadata
+			.kind_fields_mut()
+			.expect("generic pass")
+			.secondary
+			.extend([price, fee]);
+
+		pass.metadata.semantics = Some(SemanticTags {
+			total_price: Some(SemanticTagCurrencyAmount {
+				amount: Some("44".into()),
+				currency_code: Some("usd".into()),
+			}),
+			..SemanticTags::default()
+		});
+
+		assert_eq!(
+			pass.lint(),
+			[
+				Lint::UnknownCurrencyCode {
+					key: "fee".into(),
+					code: "XYZ".into()
+				},
+				Lint::UnknownCurrencyCode {
+					key: "totalPrice".into(),
+					code: "usd".into()
+				},
+			]
+		);
+	}
+
+	#[test]
+	fn lint_relevance() -> Result<(), Box<dyn std::error::Error>> {
+		let mut pass = pass();
+		pass.metadata.locations = vec![
+			Location {
+				latitude: 48.85,
+				longitude: 2.35,
+				altitude: None,
+				relevant_text: None,
+			};
+			11
+		];
+		pass.metadata.beacons = serde_json::from_value(serde_json::json!([
+			{ "proximityUUID": "e2c56db5-dffb-48d2-b060-d0f5a71096e0", "major": 1 },
+			{ "proximityUUID": "e2c56db5-dffb-48d2-b060-d0f5a71096e0", "major": 2 },
+			{ "proximityUUID": "E2C56DB5-DFFB-48D2-B060-D0F5A71096E0", "major": 1 },
+		]))?;
+
+		assert_eq!(
+			pass.lint(),
+			[
+				Lint::TooManyLocations { ignored: vec![10] },
+				Lint::DuplicateBeacon {
+					first: 0,
+					duplicate: 2
+				},
+			]
+		);
+
+		Ok(())
+	}
+
+	#[test]
+	fn lint_barcode_alt_text() {
+		let mut pass = pass();
+		pass.metadata.barcodes.push(Barcode {
+			format: BarcodeFormat::Qr,
+			message: "1234".into(),
+			message_encoding: "iso-8859-1".into(),
+			alt_text: None,
+		});
+		assert_eq!(pass.lint(), [Lint::BarcodeWithoutAltText { index: 0 }]);
+
+		pass.metadata.barcodes[0].alt_text = Some("1234".into());
+		assert!(pass.lint().is_empty());
+	}
+
+	#[test]
+	fn lint_attributed_value_html() {
+		let mut pass = pass();
+		let mut field = Field::new("website", "acme.com");
+		field.attributed_value =
+			Some(r#"Visit <a href="https://acme.com">acme.com</a> if 1 < 2"#.into());
+		pass.metadata
+			.kind_fields_mut()
+			.expect("generic pass")
+			.back
+			.push(field);
+		assert!(pass.lint().is_empty());
+
+		let field = pass.metadata.field_mut("website").expect("field exists");
+		field.attributed_value = Some("<b>Bold</b> <A HREF='https://acme.com'>link</A>".into());
+		assert_eq!(
+			pass.lint(),
+			[Lint::DisallowedHtmlTag {
+				key: "website".into(),
+				tag: "b".into()
+			}]
+		);
+	}
+
+	#[test]
+	fn lint_field_updates() {
+		let mut pass = pass();
+		let fields = pass.metadata.kind_fields_mut().expect("generic pass");
+		fields.primary.extend([
+			Field::new("gate", "A1").change_message("Gate changed to %@"),
+			Field::new("seat", "12C").change_message("Your seat changed"),
+			Field::new("", "Boarding at 8pm"),
+		]);
+
+		assert_eq!(
+			pass.lint(),
+			[
+				Lint::ChangeMessageWithoutPlaceholder { key: "seat".into() },
+				Lint::FieldWithoutKey {
+					value: "Boarding at 8pm".into()
+				},
+			]
+		);
+	}
+
+	#[test]
+	fn lint_expired() {
+		let now = Utc.with_ymd_and_hms(2024, 7, 22, 18, 30, 0).unwrap();
+		let validator = Validator::default().now(now);
+
+		let mut pass = pass();
+		pass.metadata.expiration_date = Some(now + chrono::Duration::minutes(1));
+		assert!(pass.validate(&validator).is_empty());
+
+		let expiration_date = now - chrono::Duration::days(1);
+		pass.metadata.expiration_date = Some(expiration_date);
+		assert_eq!(
+			pass.validate(&validator),
+			[Lint::Expired { expiration_date }]
+		);
+	}
+
+	#[test]
+	fn lint_images_not_displayed() {
+		let mut pass = pass();
+		pass.assets.set(Image::Logo, &Version::Standard, vec![0]);
+		pass.assets.set(Image::Thumbnail, &Version::Size3X, vec![0]);
+		assert!(pass.lint().is_empty());
+
+		pass.assets.set(Image::Strip, &Version::Size2X, vec![0]);
+		assert_eq!(
+			pass.lint(),
+			[Lint::ImageNotDisplayed {
+				image: Image::Strip,
+				kind: "generic"
+			}]
+		);
+
+		let mut ticket = Pass::new(PassConfig {
+			kind: PassKind::EventTicket(Fields::default()),
+			..testing::config()
+		});
+		ticket.assets = pass.assets;
+		assert_eq!(
+			ticket.lint(),
+			[Lint::ImageNotDisplayed {
+				image: Image::Thumbnail,
+				kind: "eventTicket"
+			}]
+		);
+	}
+
+	#[test]
+	#[cfg(feature = "image")]
+	fn lint_mismatched_image_scale() -> Result<(), Box<dyn std::error::Error>> {
+		let png = |width, height| -> Result<Vec<u8>, image::ImageError> {
+			let mut png = std::io::Cursor::new(vec![]);
+			image::RgbaImage::new(width, height).write_to(&mut png, image::ImageFormat::Png)?;
+			Ok(png.into_inner())
+		};
+
+		let mut pass = pass();
+		pass.assets.images.logo.size_x1 = Some(png(160, 50)?);
+		pass.assets.images.logo.size_x2 = Some(png(320, 120)?);
+		pass.assets.images.logo.size_x3 = Some(png(480, 150)?);
+
+		assert_eq!(
+			pass.lint(),
+			[Lint::MismatchedImageScale {
+				image: Image::Logo,
+				version: Version::Size2X,
+				expected: (320, 100),
+				actual: (320, 120),
+			}]
+		);
+
+		assert_eq!(scaled((160, 50), 3), Some((480, 150)));
+		assert_eq!(scaled((u32::MAX / 2 + 1, 1), 2), None);
+		assert_eq!(scaled((1, u32::MAX), 3), None);
+
+		Ok(())
+	}
+}
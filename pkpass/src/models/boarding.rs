@@ -0,0 +1,258 @@
+use crate::{
+	models::{
+		DateStyle, Field, Fields, PassKind, SemanticTagPersonNameComponents, SemanticTagSeat,
+		SemanticTags, TransitType,
+	},
+	Error, Result,
+};
+use chrono::{DateTime, Utc};
+
+/// Build an airline boarding pass, keeping visible fields and semantic tags in sync
+///
+/// The semantic tags let Wallet know about the flight, e.g. to show the pass when relevant.
+///
+/// ```
+/// use chrono::{TimeZone, Utc};
+/// use pkpass::{models::BoardingPassBuilder, Pass, PassConfig};
+///
+/// # fn main() -> pkpass::Result<()> {
+/// let (kind, semantics) = BoardingPassBuilder::default()
+///     .flight_code("ex123")
+///     .departure("lhr", Utc.with_ymd_and_hms(2024, 7, 22, 18, 30, 0).unwrap())
+///     .destination("mpm")
+///     .gate("1A")
+///     .seat("12C")
+///     .passenger_name("Jane", "Doe")
+///     .build()?;
+///
+/// let mut pass = Pass::new(PassConfig {
+///     organization_name: "Example Airways".into(),
+///     description: "Boarding pass for flight EX123".into(),
+///     serial_number: "EX123-12C".into(),
+///     kind,
+/// });
+/// pass.metadata.semantics = Some(semantics);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct BoardingPassBuilder {
+	flight_code: Option<String>,
+	departure_airport_code: Option<String>,
+	departure_date: Option<DateTime<Utc>>,
+	destination_airport_code: Option<String>,
+	arrival_date: Option<DateTime<Utc>>,
+	boarding_date: Option<DateTime<Utc>>,
+	gate: Option<String>,
+	seat: Option<String>,
+	passenger_name: Option<SemanticTagPersonNameComponents>,
+}
+
+impl BoardingPassBuilder {
+	/// IATA flight code, such as “EX123”
+	#[must_use]
+	pub fn flight_code(mut self, code: impl Into<String>) -> Self {
+		self.flight_code = Some(code.into());
+		self
+	}
+
+	/// IATA code of the departure airport and scheduled departure date
+	#[must_use]
+	pub fn departure(mut self, airport_code: impl Into<String>, date: DateTime<Utc>) -> Self {
+		self.departure_airport_code = Some(airport_code.into());
+		self.departure_date = Some(date);
+		self
+	}
+
+	/// IATA code of the destination airport
+	#[must_use]
+	pub fn destination(mut self, airport_code: impl Into<String>) -> Self {
+		self.destination_airport_code = Some(airport_code.into());
+		self
+	}
+
+	#[must_use]
+	pub const fn arrival_date(mut self, date: DateTime<Utc>) -> Self {
+		self.arrival_date = Some(date);
+		self
+	}
+
+	#[must_use]
+	pub const fn boarding_date(mut self, date: DateTime<Utc>) -> Self {
+		self.boarding_date = Some(date);
+		self
+	}
+
+	/// Departure gate, without the word “Gate”
+	#[must_use]
+	pub fn gate(mut self, gate: impl Into<String>) -> Self {
+		self.gate = Some(gate.into());
+		self
+	}
+
+	#[must_use]
+	pub fn seat(mut self, seat: impl Into<String>) -> Self {
+		self.seat = Some(seat.into());
+		self
+	}
+
+	#[must_use]
+	pub fn passenger_name(
+		mut self,
+		given_name: impl Into<String>,
+		family_name: impl Into<String>,
+	) -> Self {
+		self.passenger_name = Some(SemanticTagPersonNameComponents {
+			given_name: Some(given_name.into()),
+			family_name: Some(family_name.into()),
+			..SemanticTagPersonNameComponents::default()
+		});
+		self
+	}
+
+	/// Produce the boarding pass kind along with the semantic tags to set on the pass metadata
+	///
+	/// # Errors
+	///
+	/// Fails if the flight code, departure or destination is missing or if
+	/// any of them is not a valid IATA code.
+	pub fn build(self) -> Result<(PassKind, SemanticTags)> {
+		let flight_code = self
+			.flight_code
+			.ok_or(Error::MissingSemanticTag("flightCode"))?;
+		let (departure_airport_code, departure_date) = self
+			.departure_airport_code
+			.zip(self.departure_date)
+			.ok_or(Error::MissingSemanticTag("departureAirportCode"))?;
+		let destination_airport_code = self
+			.destination_airport_code
+			.ok_or(Error::MissingSemanticTag("destinationAirportCode"))?;
+
+		let mut semantics = SemanticTags::default();
+		semantics.set_flight_code(&flight_code)?;
+		semantics.set_departure_airport_code(&departure_airport_code)?;
+		semantics.set_destination_airport_code(&destination_airport_code)?;
+
+		let flight_code = semantics.flight_code.clone().unwrap_or_default();
+		let (airline_code, flight_number) = flight_code.split_at(2);
+		semantics.set_airline_code(airline_code)?;
+		semantics.flight_number = flight_number.parse().ok();
+
+		semantics.original_departure_date = Some(departure_date);
+		semantics.original_arrival_date = self.arrival_date;
+		semantics.original_boarding_date = self.boarding_date;
+		semantics.departure_gate.clone_from(&self.gate);
+		semantics.passenger_name.clone_from(&self.passenger_name);
+		if let Some(seat) = &self.seat {
+			semantics.add_seat(SemanticTagSeat::new().number(seat));
+		}
+
+		let time = |key: &str, label: &str, date: DateTime<Utc>| Field {
+			time_style: Some(DateStyle::Short),
+			..Field::new(key, date.to_rfc3339()).label(label)
+		};
+
+		let mut fields = Fields {
+			transit_type: Some(TransitType::Air),
+			..Fields::default()
+		};
+
+		fields
+			.header
+			.push(Field::new("flight", &flight_code).label("Flight"));
+		if let Some(gate) = self.gate {
+			fields.header.push(Field::new("gate", gate).label("Gate"));
+		}
+
+		let departure_airport_code = semantics.departure_airport_code.clone().unwrap_or_default();
+		let destination_airport_code = semantics
+			.destination_airport_code
+			.clone()
+			.unwrap_or_default();
+		fields
+			.primary
+			.push(Field::new("origin", departure_airport_code).label("From"));
+		fields
+			.primary
+			.push(Field::new("destination", destination_airport_code).label("To"));
+
+		if let Some(passenger_name) = &self.passenger_name {
+			let name = [&passenger_name.given_name, &passenger_name.family_name]
+				.into_iter()
+				.flatten()
+				.map(String::as_str)
+				.collect::<Vec<_>>()
+				.join(" ");
+			fields
+				.secondary
+				.push(Field::new("passenger", name).label("Passenger"));
+		}
+
+		fields
+			.auxiliary
+			.push(time("departure", "Departure", departure_date));
+		if let Some(date) = self.boarding_date {
+			fields.auxiliary.push(time("boarding", "Boarding", date));
+		}
+		if let Some(date) = self.arrival_date {
+			fields.auxiliary.push(time("arrival", "Arrival", date));
+		}
+		if let Some(seat) = self.seat {
+			fields
+				.auxiliary
+				.push(Field::new("seat", seat).label("Seat"));
+		}
+
+		Ok((PassKind::BoardingPass(fields), semantics))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use chrono::TimeZone;
+
+	#[test]
+	fn build_boarding_pass() -> Result<()> {
+		let departure = Utc.with_ymd_and_hms(2024, 7, 22, 18, 30, 0).unwrap();
+
+		let (kind, semantics) = BoardingPassBuilder::default()
+			.flight_code("ex123")
+			.departure("lhr", departure)
+			.destination("mpm")
+			.gate("1A")
+			.seat("12C")
+			.passenger_name("Jane", "Doe")
+			.build()?;
+
+		let PassKind::BoardingPass(fields) = kind else {
+			panic!("expected a boarding pass");
+		};
+		assert!(matches!(fields.transit_type, Some(TransitType::Air)));
+		assert_eq!(fields.primary[0].value, "LHR");
+		assert_eq!(fields.primary[1].value, "MPM");
+		assert_eq!(fields.secondary[0].value, "Jane Doe");
+
+		assert_eq!(semantics.airline_code.as_deref(), Some("EX"));
+		assert_eq!(semantics.flight_code.as_deref(), Some("EX123"));
+		assert_eq!(semantics.flight_number, Some(123));
+		assert_eq!(semantics.original_departure_date, Some(departure));
+		assert_eq!(semantics.departure_gate.as_deref(), Some("1A"));
+		assert_eq!(semantics.seats[0].seat_number.as_deref(), Some("12C"));
+
+		Ok(())
+	}
+
+	#[test]
+	fn boarding_pass_requires_departure() {
+		let result = BoardingPassBuilder::default()
+			.flight_code("EX123")
+			.destination("MPM")
+			.build();
+
+		assert!(matches!(
+			result,
+			Err(Error::MissingSemanticTag("departureAirportCode"))
+		));
+	}
+}
@@ -17,6 +17,20 @@ pub enum PassKind {
 	StoreCard(Fields),
 }
 
+/// Generic is the fallback style, for passes matching none of the specific kinds
+impl Default for PassKind {
+	fn default() -> Self {
+		Self::Generic(Fields::default())
+	}
+}
+
+/// A generic pass with the given fields
+impl From<Fields> for PassKind {
+	fn from(fields: Fields) -> Self {
+		Self::Generic(fields)
+	}
+}
+
 /// <https://developer.apple.com/documentation/walletpasses/passfields>
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase", deny_unknown_fields)]
@@ -44,6 +58,7 @@ pub struct Fields {
 	/// The type of transit for a boarding pass. This key is invalid for other types of passes.
 	// TODO: doc
 	/// PANIC: Only valid for a boarding pass
+	#[serde(skip_serializing_if = "Option::is_none")]
 	pub transit_type: Option<TransitType>,
 }
 
@@ -1,7 +1,29 @@
 use crate::{
-	models::{Field, Fields, Metadata, PassKind, RowBehaviour},
-	PassConfig,
+	models::{
+		semantics, Barcode, BarcodeFormat, Device, Field, Fields, Location, Metadata, Nfc,
+		PassKind, RowBehaviour, SemanticTagCurrencyAmount, SemanticTagLocation, SemanticTagSeat,
+		SemanticTags,
+	},
+	Error, PassConfig, Result,
 };
+use std::collections::BTreeMap;
+
+/// Rebuild every object of `value` with its keys in sorted order
+fn sort_keys(value: serde_json::Value) -> serde_json::Value {
+	match value {
+		serde_json::Value::Object(object) => {
+			let sorted = object
+				.into_iter()
+				.map(|(key, value)| (key, sort_keys(value)))
+				.collect::<BTreeMap<_, _>>();
+			serde_json::Value::Object(sorted.into_iter().collect())
+		}
+		serde_json::Value::Array(values) => {
+			serde_json::Value::Array(values.into_iter().map(sort_keys).collect())
+		}
+		value => value,
+	}
+}
 
 impl Metadata {
 	pub(crate) fn new(
@@ -12,6 +34,7 @@ impl Metadata {
 			kind,
 		}: PassConfig,
 	) -> Self {
+		#[allow(deprecated)]
 		let mut this = Self {
 			format_version: 1,
 
@@ -37,6 +60,7 @@ impl Metadata {
 			associated_store_identifiers: Vec::default(),
 
 			barcodes: Vec::default(),
+			barcode: None,
 			beacons: Vec::default(),
 			expiration_date: None,
 			grouping_identifier: None,
@@ -44,6 +68,7 @@ impl Metadata {
 			logo_text: None,
 			max_distance: None,
 			nfc: None,
+			preferred_style_schemes: Vec::default(),
 			relevant_date: None,
 			// TODO: huge empty thingy
 			semantics: None,
@@ -62,10 +87,330 @@ impl Metadata {
 			PassKind::EventTicket(fields) => this.event_ticket = Some(fields),
 			PassKind::Generic(fields) => this.generic = Some(fields),
 			PassKind::StoreCard(fields) => this.store_card = Some(fields),
-		};
+		}
 
 		this
 	}
+
+	/// The version of the file format, always 1
+	#[must_use]
+	pub const fn format_version(&self) -> u64 {
+		self.format_version
+	}
+
+	/// The description used by accessibility technologies
+	#[must_use]
+	pub fn description(&self) -> &str {
+		&self.description
+	}
+
+	/// The serial number, unique among passes of the same pass type
+	#[must_use]
+	pub fn serial_number(&self) -> &str {
+		&self.serial_number
+	}
+
+	/// The pass type identifier, filled from the [`Identity`](crate::sign::Identity) when writing
+	#[must_use]
+	pub fn pass_type_identifier(&self) -> &str {
+		&self.pass_type_identifier
+	}
+
+	/// The team identifier, filled from the [`Identity`](crate::sign::Identity) when writing
+	#[must_use]
+	pub fn team_identifier(&self) -> &str {
+		&self.team_identifier
+	}
+
+	/// Barcodes by order of preference, or the deprecated single `barcode` if there are none
+	#[must_use]
+	pub fn barcodes(&self) -> &[Barcode] {
+		#[allow(deprecated)]
+		if self.barcodes.is_empty() {
+			self.barcode.as_slice()
+		} else {
+			&self.barcodes
+		}
+	}
+
+	/// The barcode Wallet tries first, it uses the first one the device can
+	/// display, see [`Metadata::barcode_for`]
+	#[must_use]
+	pub fn primary_barcode(&self) -> Option<&Barcode> {
+		self.barcodes().first()
+	}
+
+	/// The barcode `device` displays, the first of a format it supports
+	#[must_use]
+	pub fn barcode_for(&self, device: Device) -> Option<&Barcode> {
+		self.barcodes()
+			.iter()
+			.find(|barcode| barcode.format.is_supported_on(device))
+	}
+
+	/// Make `barcode` the one Wallet tries first, keeping the others as fallbacks
+	pub fn set_primary_barcode(&mut self, barcode: Barcode) {
+		self.barcodes.insert(0, barcode);
+	}
+
+	/// Serialize to a `pass.json` suitable for reading and diffing
+	///
+	/// Keys are sorted and indented with 2 spaces, like Apple's sample passes.
+	/// [`Pass::write`](crate::Pass::write) keeps using the compact form.
+	///
+	/// # Errors
+	///
+	/// Fails if the metadata could not be serialized.
+	pub fn to_pretty_json(&self) -> Result<String> {
+		Ok(serde_json::to_string_pretty(&self.to_sorted_value()?)?)
+	}
+
+	/// Serialize to a json value whose objects have their keys sorted
+	///
+	/// Any crate of the dependency graph can enable the `preserve_order`
+	/// feature of `serde_json`, which keeps fields in declaration order, so
+	/// keys are sorted explicitly rather than relying on its default maps.
+	pub(crate) fn to_sorted_value(&self) -> Result<serde_json::Value> {
+		Ok(sort_keys(serde_json::to_value(self)?))
+	}
+
+	/// Set the balance of a store card in its `balance` semantic tag and, when
+	/// `field_key` is given, in the primary field with this key, adding it if
+	/// missing, so both stay in sync across updates
+	///
+	/// The currency code is uppercased, such as `eur` to `EUR`.
+	///
+	/// # Errors
+	///
+	/// Fails if the pass is not a store card or if the currency is not an ISO 4217 code.
+	pub fn set_store_card_balance(
+		&mut self,
+		amount: &str,
+		currency: &str,
+		field_key: Option<&str>,
+	) -> Result<()> {
+		let currency = currency.trim().to_ascii_uppercase();
+		if !semantics::is_currency_code(&currency) {
+			return Err(Error::InvalidCurrencyCode(currency));
+		}
+		let pass = self.kinds().first().copied().unwrap_or("pass");
+		let Some(fields) = &mut self.store_card else {
+			return Err(Error::SemanticTagNotApplicable {
+				key: "balance",
+				pass,
+			});
+		};
+
+		if let Some(key) = field_key {
+			if !fields.primary.iter().any(|field| field.key == key) {
+				fields.primary.push(Field::new(key, ""));
+			}
+			for field in fields.primary.iter_mut().filter(|field| field.key == key) {
+				amount.clone_into(&mut field.value);
+				field.currency_code = Some(currency.clone());
+			}
+		}
+
+		self.semantics
+			.get_or_insert_with(SemanticTags::default)
+			.balance = Some(SemanticTagCurrencyAmount {
+			amount: Some(amount.into()),
+			currency_code: Some(currency),
+		});
+
+		Ok(())
+	}
+
+	/// Wallet only considers the first 10 relevant locations
+	pub const MAX_LOCATIONS: usize = 10;
+
+	/// Add a relevant location, such as one of a store, see [`Location::new`]
+	///
+	/// # Errors
+	///
+	/// Fails with [`Error::TooManyLocations`] if the pass has 10 locations already.
+	pub fn add_location(&mut self, location: Location) -> Result<()> {
+		if self.locations.len() >= Self::MAX_LOCATIONS {
+			return Err(Error::TooManyLocations);
+		}
+		self.locations.push(location);
+		Ok(())
+	}
+
+	/// Keys of the pass styles set, such as `storeCard`
+	pub(crate) fn kinds(&self) -> Vec<&'static str> {
+		[
+			("boardingPass", &self.boarding_pass),
+			("coupon", &self.coupon),
+			("eventTicket", &self.event_ticket),
+			("generic", &self.generic),
+			("storeCard", &self.store_card),
+		]
+		.into_iter()
+		.filter_map(|(key, fields)| fields.as_ref().map(|_| key))
+		.collect()
+	}
+
+	/// Check exactly one of the pass styles is set, which deserialized metadata doesn't ensure
+	pub(crate) fn check_kind(&self) -> Result<()> {
+		let kinds = self.kinds();
+		match kinds.len() {
+			0 => Err(Error::MissingPassKind),
+			1 => Ok(()),
+			_ => Err(Error::MultiplePassKinds(kinds)),
+		}
+	}
+
+	/// The pass style and its fields, a generic pass without fields if none is set
+	///
+	/// If several styles are set, the first in alphabetical order wins.
+	#[must_use]
+	pub fn kind(&self) -> PassKind {
+		[
+			self.boarding_pass.clone().map(PassKind::BoardingPass),
+			self.coupon.clone().map(PassKind::Coupon),
+			self.event_ticket.clone().map(PassKind::EventTicket),
+			self.generic.clone().map(PassKind::Generic),
+			self.store_card.clone().map(PassKind::StoreCard),
+		]
+		.into_iter()
+		.flatten()
+		.next()
+		.unwrap_or_default()
+	}
+
+	/// The [`PassConfig`] this metadata could have been created from
+	///
+	/// Useful to build a new pass from an existing one.
+	#[must_use]
+	pub fn to_config(&self) -> PassConfig {
+		PassConfig {
+			organization_name: self.organization_name.clone(),
+			description: self.description.clone(),
+			serial_number: self.serial_number.clone(),
+			kind: self.kind(),
+		}
+	}
+
+	/// The fields of whichever pass style is set, to edit a pass regardless of its kind
+	///
+	/// `None` only if no style is set. See [`Metadata::fields_mut`] to iterate over every field.
+	pub fn kind_fields_mut(&mut self) -> Option<&mut Fields> {
+		[
+			&mut self.boarding_pass,
+			&mut self.coupon,
+			&mut self.event_ticket,
+			&mut self.generic,
+			&mut self.store_card,
+		]
+		.into_iter()
+		.find_map(Option::as_mut)
+	}
+
+	/// The field with the given key, whatever its region
+	pub fn field_mut(&mut self, key: &str) -> Option<&mut Field> {
+		self.fields_mut().find(|field| field.key == key)
+	}
+
+	/// Every field of the pass, whatever its kind and region
+	pub fn fields(&self) -> impl Iterator<Item = &Field> {
+		[
+			&self.boarding_pass,
+			&self.coupon,
+			&self.event_ticket,
+			&self.generic,
+			&self.store_card,
+		]
+		.into_iter()
+		.flatten()
+		.flat_map(Fields::iter)
+	}
+
+	/// Every field of the pass, whatever its kind and region
+	///
+	/// Useful for bulk edits, such as replacing a placeholder in every value.
+	pub fn fields_mut(&mut self) -> impl Iterator<Item = &mut Field> {
+		[
+			&mut self.boarding_pass,
+			&mut self.coupon,
+			&mut self.event_ticket,
+			&mut self.generic,
+			&mut self.store_card,
+		]
+		.into_iter()
+		.flatten()
+		.flat_map(Fields::iter_mut)
+	}
+}
+
+impl BarcodeFormat {
+	/// Whether `device` can display barcodes of this format, Apple Watch can't show Code 128
+	#[must_use]
+	pub const fn is_supported_on(&self, device: Device) -> bool {
+		!matches!((self, device), (Self::Pdf128, Device::AppleWatch))
+	}
+}
+
+impl Barcode {
+	/// PDF417 barcode of binary data, each byte being the Latin-1 character of the same value
+	#[must_use]
+	pub fn pdf417_binary(bytes: &[u8]) -> Self {
+		Self {
+			format: BarcodeFormat::Pdf417,
+			message: bytes.iter().copied().map(char::from).collect(),
+			message_encoding: "iso-8859-1".into(),
+			alt_text: None,
+		}
+	}
+
+	/// Binary data of a Latin-1 encoded barcode, such as one made by [`Barcode::pdf417_binary`]
+	///
+	/// `None` if the encoding is not `iso-8859-1` or the message has characters outside of Latin-1.
+	#[must_use]
+	pub fn latin1_bytes(&self) -> Option<Vec<u8>> {
+		if !self.message_encoding.eq_ignore_ascii_case("iso-8859-1") {
+			return None;
+		}
+		self.message.chars().map(|c| u8::try_from(c).ok()).collect()
+	}
+}
+
+impl Nfc {
+	/// Longest message in bytes, the system truncates longer ones
+	pub const MAX_MESSAGE_LEN: usize = 64;
+
+	/// Payload for Apple Pay terminals, not requiring authentication
+	///
+	/// # Errors
+	///
+	/// Fails with [`Error::NfcMessageTooLong`] if the UTF-8 encoded message is longer than 64 bytes.
+	pub fn new(
+		encryption_public_key: impl Into<String>,
+		message: impl Into<String>,
+	) -> Result<Self> {
+		let mut nfc = Self {
+			encryption_public_key: encryption_public_key.into(),
+			message: String::new(),
+			requires_authentication: None,
+		};
+		nfc.set_message(message)?;
+		Ok(nfc)
+	}
+
+	/// Replace the payload transmitted to the Apple Pay terminal
+	///
+	/// # Errors
+	///
+	/// Fails with [`Error::NfcMessageTooLong`] if the UTF-8 encoded message is
+	/// longer than 64 bytes, the message is then left unchanged.
+	pub fn set_message(&mut self, message: impl Into<String>) -> Result<()> {
+		let message = message.into();
+		if message.len() > Self::MAX_MESSAGE_LEN {
+			return Err(Error::NfcMessageTooLong(message.len()));
+		}
+		self.message = message;
+		Ok(())
+	}
 }
 
 impl Field {
@@ -105,10 +450,229 @@ impl Field {
 		self
 	}
 
+	/// Notify of updates changing the value of the field, with `%@` replaced
+	/// by the new value, such as “Gate changed to %@”
+	///
+	/// Wallet only notifies when the field keeps the same key across updates.
+	#[must_use]
+	pub fn change_message(mut self, message: impl Into<String>) -> Self {
+		self.change_message = Some(message.into());
+		self
+	}
+
 	// TODO: more fields
 }
 
 impl Fields {
 	// TODO
 	// pub fn add_secondary(mut self)
+
+	/// Whether no region has a field and no transit type is set
+	#[must_use]
+	pub fn is_empty(&self) -> bool {
+		self.iter().next().is_none() && self.transit_type.is_none()
+	}
+
+	/// Fields of every region, from the header to the back of the pass
+	pub fn iter(&self) -> impl Iterator<Item = &Field> {
+		self.header
+			.iter()
+			.chain(&self.primary)
+			.chain(&self.secondary)
+			.chain(&self.auxiliary)
+			.chain(&self.back)
+	}
+
+	/// Fields of every region, from the header to the back of the pass
+	pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut Field> {
+		self.header
+			.iter_mut()
+			.chain(&mut self.primary)
+			.chain(&mut self.secondary)
+			.chain(&mut self.auxiliary)
+			.chain(&mut self.back)
+	}
+}
+
+impl SemanticTags {
+	pub fn add_seat(&mut self, seat: SemanticTagSeat) {
+		self.seats.push(seat);
+	}
+
+	/// Set the IATA airline code, such as “EX”
+	///
+	/// # Errors
+	///
+	/// Fails if the uppercased code is not two letters or digits.
+	pub fn set_airline_code(&mut self, code: &str) -> Result<()> {
+		self.airline_code = Some(iata_code(code, semantics::is_airline_code)?);
+		Ok(())
+	}
+
+	/// Set the IATA flight code, such as “EX123”
+	///
+	/// # Errors
+	///
+	/// Fails if the uppercased code is not an airline code followed by one to four digits.
+	pub fn set_flight_code(&mut self, code: &str) -> Result<()> {
+		self.flight_code = Some(iata_code(code, semantics::is_flight_code)?);
+		Ok(())
+	}
+
+	/// Set the IATA code of the departure airport, such as “LHR”
+	///
+	/// # Errors
+	///
+	/// Fails if the uppercased code is not three letters.
+	pub fn set_departure_airport_code(&mut self, code: &str) -> Result<()> {
+		self.departure_airport_code = Some(iata_code(code, semantics::is_airport_code)?);
+		Ok(())
+	}
+
+	/// Set the IATA code of the destination airport, such as “MPM”
+	///
+	/// # Errors
+	///
+	/// Fails if the uppercased code is not three letters.
+	pub fn set_destination_airport_code(&mut self, code: &str) -> Result<()> {
+		self.destination_airport_code = Some(iata_code(code, semantics::is_airport_code)?);
+		Ok(())
+	}
+}
+
+fn iata_code(code: &str, is_valid: fn(&str) -> bool) -> Result<String> {
+	let code = code.trim().to_ascii_uppercase();
+	if is_valid(&code) {
+		Ok(code)
+	} else {
+		Err(Error::InvalidIataCode(code))
+	}
+}
+
+/// Seat builder
+///
+/// ```
+/// use pkpass::models::{SemanticTagSeat, SemanticTags};
+///
+/// let mut semantics = SemanticTags::default();
+/// semantics.add_seat(
+///     SemanticTagSeat::new()
+///         .section("Floor")
+///         .row("K")
+///         .number("12")
+///         .seat_type("Reserved seating"),
+/// );
+/// ```
+impl SemanticTagSeat {
+	#[must_use]
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	#[must_use]
+	pub fn description(mut self, description: impl Into<String>) -> Self {
+		self.seat_description = Some(description.into());
+		self
+	}
+
+	#[must_use]
+	pub fn identifier(mut self, identifier: impl Into<String>) -> Self {
+		self.seat_identifier = Some(identifier.into());
+		self
+	}
+
+	#[must_use]
+	pub fn number(mut self, number: impl Into<String>) -> Self {
+		self.seat_number = Some(number.into());
+		self
+	}
+
+	#[must_use]
+	pub fn row(mut self, row: impl Into<String>) -> Self {
+		self.seat_row = Some(row.into());
+		self
+	}
+
+	#[must_use]
+	pub fn section(mut self, section: impl Into<String>) -> Self {
+		self.seat_section = Some(section.into());
+		self
+	}
+
+	#[must_use]
+	pub fn seat_type(mut self, seat_type: impl Into<String>) -> Self {
+		self.seat_type = Some(seat_type.into());
+		self
+	}
+}
+
+/// Location builder
+///
+/// ```
+/// use pkpass::models::Location;
+///
+/// let store = Location::new(48.8566, 2.3522)?
+///     .altitude(35.0)
+///     .relevant_text("Store nearby on Rue de Rivoli");
+/// # Ok::<(), pkpass::Error>(())
+/// ```
+impl Location {
+	/// Location at the given coordinates, in degrees
+	///
+	/// # Errors
+	///
+	/// Fails with [`Error::InvalidCoordinate`] if the latitude is not within
+	/// -90 and 90 or the longitude within -180 and 180.
+	pub fn new(latitude: f64, longitude: f64) -> Result<Self> {
+		for (axis, value, max) in [
+			("latitude", latitude, 90.0),
+			("longitude", longitude, 180.0),
+		] {
+			if !(-max..=max).contains(&value) {
+				return Err(Error::InvalidCoordinate { axis, value, max });
+			}
+		}
+
+		Ok(Self {
+			latitude,
+			longitude,
+			altitude: None,
+			relevant_text: None,
+		})
+	}
+
+	/// Set the altitude, in meters
+	#[must_use]
+	pub const fn altitude(mut self, altitude: f64) -> Self {
+		self.altitude = Some(altitude);
+		self
+	}
+
+	/// Set the text displayed on the lock screen when the pass is relevant
+	#[must_use]
+	pub fn relevant_text(mut self, text: impl Into<String>) -> Self {
+		self.relevant_text = Some(text.into());
+		self
+	}
+}
+
+impl From<&Location> for SemanticTagLocation {
+	fn from(location: &Location) -> Self {
+		Self {
+			latitude: location.latitude,
+			longitude: location.longitude,
+		}
+	}
+}
+
+/// Relevant location at the same coordinates, without altitude nor relevant text
+impl From<&SemanticTagLocation> for Location {
+	fn from(location: &SemanticTagLocation) -> Self {
+		Self {
+			latitude: location.latitude,
+			longitude: location.longitude,
+			altitude: None,
+			relevant_text: None,
+		}
+	}
 }
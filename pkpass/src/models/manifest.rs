@@ -1,8 +1,8 @@
-use openssl::sha::Sha1;
+use crate::digest::{self, sha256, Sha256};
 use serde::{Deserialize, Serialize};
 use std::{
-	collections::{hash_map::Entry, HashMap},
-	io,
+	collections::{BTreeMap, HashMap},
+	fmt, io, mem,
 	str::FromStr,
 };
 use unic_langid::LanguageIdentifier;
@@ -11,55 +11,175 @@ use unic_langid::LanguageIdentifier;
 #[serde(transparent)]
 pub struct Manifest {
 	assets: HashMap<String, String>,
+	#[serde(skip)]
+	hash: ManifestHashPolicy,
 }
 
-impl Manifest {
-	pub(crate) fn add_file(&mut self, name: &str, data: &[u8]) {
-		match self.assets.entry(name.to_owned()) {
-			Entry::Occupied(_) => todo!(),
-			Entry::Vacant(place) => {
-				place.insert(sha1(data));
-			}
+/// Digest of the files listed in `manifest.json`
+///
+/// Apple documents SHA-1 and every Wallet version accepts it, SHA-256 is
+/// only for verifiers requiring a modern digest.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ManifestHashPolicy {
+	#[default]
+	Sha1,
+	Sha256,
+}
+
+impl ManifestHashPolicy {
+	/// Hex digest of `data`
+	#[must_use]
+	pub fn digest(self, data: &[u8]) -> String {
+		match self {
+			Self::Sha1 => hex::encode(digest::sha1(data)),
+			Self::Sha256 => hex::encode(sha256(data)),
 		}
 	}
 
-	pub(crate) fn verify_file(&self, name: &str, data: &[u8]) -> bool {
-		self.assets
-			.get(name)
-			.map_or(false, |sha| *sha == sha1(data))
+	/// Policy a hex digest was made with, told by its length
+	const fn of(digest: &str) -> Self {
+		if digest.len() == 64 {
+			Self::Sha256
+		} else {
+			Self::Sha1
+		}
 	}
 }
 
-fn sha1(data: &[u8]) -> String {
-	let mut hasher = Sha1::new();
-	hasher.update(data);
-	hex::encode(hasher.finish())
+impl Manifest {
+	pub(crate) fn new(hash: ManifestHashPolicy) -> Self {
+		Self {
+			assets: HashMap::new(),
+			hash,
+		}
+	}
+
+	/// List `data` under `name`, replacing the digest of a file added before
+	/// with the same name, so that the manifest matches the last content
+	pub(crate) fn add_file(&mut self, name: &str, data: &[u8]) {
+		self.assets.insert(name.to_owned(), self.hash.digest(data));
+	}
+
+	/// Check `data` against its digest, whichever of SHA-1 or SHA-256 it is
+	pub(crate) fn verify_file(&self, name: &str, data: &[u8]) -> bool {
+		let Some(expected) = self.assets.get(name) else {
+			trace_event!(
+				debug,
+				file = name,
+				ok = false,
+				"file is not in the manifest"
+			);
+			return false;
+		};
+		let actual = ManifestHashPolicy::of(expected).digest(data);
+		// not secret, but constant-time like the rest of the verification
+		let ok = digest::eq(expected.as_bytes(), actual.as_bytes());
+		trace_event!(debug, file = name, digest = %actual, ok, "verified manifest digest");
+		ok
+	}
 }
 
-#[derive(Debug, PartialEq, Eq, Hash)]
+/// Resolution of an image, written as a suffix of its file name such as `logo@2x.png`
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum Version {
 	Standard,
 	Size2X,
 	Size3X,
 }
 
+impl Version {
+	/// File name suffix, including the `@`, empty for the standard resolution
+	#[must_use]
+	pub const fn suffix(&self) -> &'static str {
+		match self {
+			Self::Standard => "",
+			Self::Size2X => "@2x",
+			Self::Size3X => "@3x",
+		}
+	}
+
+	/// Split a file stem such as `logo@2x` into its name and version
+	pub(crate) fn split(stem: &str) -> Result<(&str, Self), ()> {
+		let (name, suffix) = stem.find('@').map_or((stem, ""), |at| stem.split_at(at));
+		Ok((name, suffix.parse()?))
+	}
+}
+
 impl FromStr for Version {
 	type Err = ();
 
+	/// Parse a file name suffix, as returned by [`Version::suffix`]
 	fn from_str(s: &str) -> Result<Self, Self::Err> {
 		match s {
 			"" => Ok(Self::Standard),
-			"2x" => Ok(Self::Size2X),
-			"3x" => Ok(Self::Size3X),
+			"@2x" => Ok(Self::Size2X),
+			"@3x" => Ok(Self::Size3X),
 			_ => Err(()),
 		}
 	}
 }
 
+impl fmt::Display for Version {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.write_str(self.suffix())
+	}
+}
+
+/// The images a pass can display
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Image {
+	Icon,
+	Background,
+	Footer,
+	Logo,
+	Strip,
+	Thumbnail,
+	/// Artwork of the poster layout of event tickets
+	Artwork,
+}
+
+impl Image {
+	pub const ALL: [Self; 7] = [
+		Self::Icon,
+		Self::Background,
+		Self::Footer,
+		Self::Logo,
+		Self::Strip,
+		Self::Thumbnail,
+		Self::Artwork,
+	];
+
+	/// File name of the image without version suffix and extension
+	#[must_use]
+	pub const fn name(self) -> &'static str {
+		match self {
+			Self::Icon => "icon",
+			Self::Background => "background",
+			Self::Footer => "footer",
+			Self::Logo => "logo",
+			Self::Strip => "strip",
+			Self::Thumbnail => "thumbnail",
+			Self::Artwork => "artwork",
+		}
+	}
+}
+
+impl FromStr for Image {
+	type Err = ();
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		Self::ALL
+			.into_iter()
+			.find(|image| image.name() == s)
+			.ok_or(())
+	}
+}
+
 #[derive(Debug, Clone, Default)]
 pub struct Assets {
 	pub images: ImageAssets,
 	localized: HashMap<LanguageIdentifier, LocalizedAssets>,
+	raw: BTreeMap<String, Vec<u8>>,
 }
 
 impl Assets {
@@ -67,6 +187,62 @@ impl Assets {
 		self.localized.entry(lang).or_default()
 	}
 
+	/// Assets of a language given as a tag such as `fr`, `FR` or `fr_FR`, see [`Assets::parse_language`]
+	///
+	/// # Errors
+	///
+	/// Fails with [`Error::InvalidLanguageTag`](crate::Error::InvalidLanguageTag) if the tag is not valid.
+	pub fn get_by_tag(&mut self, tag: &str) -> crate::Result<&mut LocalizedAssets> {
+		Ok(self.get(Self::parse_language(tag)?))
+	}
+
+	/// Parse a language tag to its canonical form, naming the `.lproj` directory
+	/// Wallet matches, such as `fr-FR` for `fr_FR` or `zh-Hans` for `ZH-HANS`
+	///
+	/// # Errors
+	///
+	/// Fails with [`Error::InvalidLanguageTag`](crate::Error::InvalidLanguageTag) if the tag is not valid.
+	pub fn parse_language(tag: &str) -> crate::Result<LanguageIdentifier> {
+		LanguageIdentifier::from_str(tag.trim())
+			.ok()
+			.filter(|lang| !lang.language.is_empty())
+			.ok_or_else(|| crate::Error::InvalidLanguageTag(tag.into()))
+	}
+
+	/// Set a version of an image displayed whatever the device language
+	pub fn set(&mut self, image: Image, version: &Version, png: Vec<u8>) {
+		self.images.set(image, version, png);
+	}
+
+	/// Set a version of an image displayed when the device is in `lang`
+	pub fn set_localized(
+		&mut self,
+		lang: LanguageIdentifier,
+		image: Image,
+		version: &Version,
+		png: Vec<u8>,
+	) {
+		self.get(lang).images.set(image, version, png);
+	}
+
+	/// Assets of a language, if the pass is localized in it
+	#[must_use]
+	pub fn localized(&self, lang: &LanguageIdentifier) -> Option<&LocalizedAssets> {
+		self.localized.get(lang)
+	}
+
+	/// Remove every asset for the given language, returning them if any
+	pub fn remove_language(&mut self, lang: &LanguageIdentifier) -> Option<LocalizedAssets> {
+		self.localized.remove(lang)
+	}
+
+	/// Remove all images, localizations and raw files
+	pub fn clear(&mut self) {
+		self.images = ImageAssets::default();
+		self.localized.clear();
+		self.raw.clear();
+	}
+
 	pub(crate) fn get_mut(&mut self, path: &str) -> io::Result<&mut Vec<u8>> {
 		// path can be escaped?
 		match path.split_once('/') {
@@ -91,6 +267,89 @@ impl Assets {
 		}
 	}
 
+	/// SHA-1 of every asset, as written in the manifest, by path in the archive
+	#[must_use]
+	pub fn digests(&self) -> BTreeMap<String, String> {
+		self.paths()
+			.into_iter()
+			.map(|(path, content)| (path, ManifestHashPolicy::Sha1.digest(content)))
+			.collect()
+	}
+
+	/// The lowest resolution of every image at the root of the archive,
+	/// preferring `@1x`, such as to render a preview
+	///
+	/// Images without any version are left out.
+	#[must_use]
+	pub fn images_map(&self) -> BTreeMap<Image, &[u8]> {
+		Image::ALL
+			.into_iter()
+			.filter_map(|image| {
+				let asset = self.images.image(image);
+				let content = asset
+					.size_x1
+					.as_ref()
+					.or(asset.size_x2.as_ref())
+					.or(asset.size_x3.as_ref())?;
+				Some((image, content.as_slice()))
+			})
+			.collect()
+	}
+
+	/// Files the typed assets don't model, by path in the archive, see
+	/// [`Pass::insert_raw_asset`](crate::Pass::insert_raw_asset)
+	#[must_use]
+	pub const fn raw(&self) -> &BTreeMap<String, Vec<u8>> {
+		&self.raw
+	}
+
+	/// Store `bytes` as the typed asset of `path` if it is one, as is otherwise
+	pub(crate) fn insert_raw(&mut self, path: String, bytes: Vec<u8>) {
+		match self.get_mut(&path) {
+			Ok(asset) => *asset = bytes,
+			Err(_) => {
+				self.raw.insert(path, bytes);
+			}
+		}
+	}
+
+	/// Whether the pass has no image, localization nor raw file
+	#[must_use]
+	pub fn is_empty(&self) -> bool {
+		self.paths().is_empty()
+	}
+
+	/// Hex-encoded SHA-256 of every asset, stable across identical assets
+	///
+	/// Covers the path and SHA-256 of every asset sorted by path, like
+	/// [`Pass::content_hash`](crate::Pass::content_hash) but without `pass.json`.
+	#[must_use]
+	pub fn content_hash(&self) -> String {
+		let mut hasher = Sha256::new();
+		self.hash_into(&mut hasher);
+		hex::encode(hasher.finish())
+	}
+
+	/// Feed the path and SHA-256 of every asset to `hasher`, sorted by path
+	pub(crate) fn hash_into(&self, hasher: &mut Sha256) {
+		for (path, content) in self.sorted_paths() {
+			hasher.update(path.as_bytes());
+			hasher.update(b"\0");
+			hasher.update(&sha256(content));
+		}
+	}
+
+	fn sorted_paths(&self) -> Vec<(String, &Vec<u8>)> {
+		let mut paths = self.paths();
+		paths.sort_by(|(a, _), (b, _)| a.cmp(b));
+		paths
+	}
+
+	/// Images at the root of the archive followed by the localized ones
+	pub(crate) fn image_sets(&self) -> impl Iterator<Item = &ImageAssets> {
+		std::iter::once(&self.images).chain(self.localized.values().map(|l| &l.images))
+	}
+
 	pub(crate) fn paths(&self) -> Vec<(String, &Vec<u8>)> {
 		let mut v = self.images.paths();
 		for (lang, lasst) in &self.localized {
@@ -98,10 +357,21 @@ impl Assets {
 				v.push((format!("{lang}.lproj/{path}"), ct));
 			}
 		}
+		v.extend(self.raw.iter().map(|(path, ct)| (path.clone(), ct)));
 		v
 	}
 }
 
+/// Assets are equal when they hold the same files with the same content,
+/// whatever empty languages they keep around
+impl PartialEq for Assets {
+	fn eq(&self, other: &Self) -> bool {
+		self.sorted_paths() == other.sorted_paths()
+	}
+}
+
+impl Eq for Assets {}
+
 #[derive(Debug, Clone, Default)]
 pub struct ImageAssets {
 	/// The icon (icon.png)
@@ -116,6 +386,8 @@ pub struct ImageAssets {
 	pub strip: ImageAsset,
 	/// The thumbnail image (thumbnail.png)
 	pub thumbnail: ImageAsset,
+	/// The artwork image of poster event tickets (artwork.png)
+	pub artwork: ImageAsset,
 }
 
 impl ImageAssets {
@@ -126,39 +398,59 @@ impl ImageAssets {
 				"images are expected in png format",
 			)
 		})?;
-		let (name, version) = name.split_once('@').unwrap_or((name, ""));
-
-		let version = Version::from_str(version).map_err(|()| {
+		let (name, version) = Version::split(name).map_err(|()| {
 			io::Error::new(
 				io::ErrorKind::InvalidData,
 				"image version is not recognized",
 			)
 		})?;
 
-		let asset = match name {
-			"background" => &mut self.background,
-			"footer" => &mut self.footer,
-			"icon" => &mut self.icon,
-			"logo" => &mut self.logo,
-			"strip" => &mut self.strip,
-			"thumbnail" => &mut self.thumbnail,
-			_ => return Err(io::Error::new(io::ErrorKind::InvalidData, "")),
-		};
+		let image = Image::from_str(name)
+			.map_err(|()| io::Error::new(io::ErrorKind::InvalidData, "image is not recognized"))?;
+
+		Ok(self.image_mut(image).get_mut(&version))
+	}
 
-		Ok(asset.get_mut(&version))
+	#[must_use]
+	pub const fn image(&self, image: Image) -> &ImageAsset {
+		match image {
+			Image::Icon => &self.icon,
+			Image::Background => &self.background,
+			Image::Footer => &self.footer,
+			Image::Logo => &self.logo,
+			Image::Strip => &self.strip,
+			Image::Thumbnail => &self.thumbnail,
+			Image::Artwork => &self.artwork,
+		}
+	}
+
+	pub fn image_mut(&mut self, image: Image) -> &mut ImageAsset {
+		match image {
+			Image::Icon => &mut self.icon,
+			Image::Background => &mut self.background,
+			Image::Footer => &mut self.footer,
+			Image::Logo => &mut self.logo,
+			Image::Strip => &mut self.strip,
+			Image::Thumbnail => &mut self.thumbnail,
+			Image::Artwork => &mut self.artwork,
+		}
+	}
+
+	/// Set a version of an image, replacing the previous content
+	pub fn set(&mut self, image: Image, version: &Version, png: Vec<u8>) {
+		*self.image_mut(image).get_mut(version) = png;
+	}
+
+	/// Remove every version of an image, returning the previous content
+	pub fn remove(&mut self, image: Image) -> ImageAsset {
+		mem::take(self.image_mut(image))
 	}
 
 	pub(crate) fn paths(&self) -> Vec<(String, &Vec<u8>)> {
 		let mut paths = vec![];
-		for (name, image) in [
-			("icon", &self.icon),
-			("background", &self.background),
-			("footer", &self.footer),
-			("logo", &self.logo),
-			("strip", &self.strip),
-			("thumbnail", &self.thumbnail),
-		] {
-			for (v, ct) in image.versions() {
+		for image in Image::ALL {
+			let name = image.name();
+			for (v, ct) in self.image(image).versions() {
 				paths.push((format!("{name}{v}.png"), ct));
 			}
 		}
@@ -180,6 +472,42 @@ impl LocalizedAssets {
 		}
 	}
 
+	/// Append a translation to `pass.strings`
+	pub fn insert_string(&mut self, key: &str, value: &str) {
+		let escape = |s: &str| {
+			s.replace('\\', "\\\\")
+				.replace('"', "\\\"")
+				.replace('\n', "\\n")
+		};
+
+		let strings = self.strings.get_or_insert_with(Default::default);
+		if !strings.is_empty() && !strings.ends_with(b"\n") {
+			strings.push(b'\n');
+		}
+		strings.extend_from_slice(
+			format!("\"{}\" = \"{}\";\n", escape(key), escape(value)).as_bytes(),
+		);
+	}
+
+	/// Look a key up in `pass.strings`, the last entry wins when a key is repeated
+	///
+	/// Only UTF-8 files with one `"key" = "value";` entry per line are understood.
+	#[must_use]
+	pub fn translate(&self, key: &str) -> Option<String> {
+		let strings = std::str::from_utf8(self.strings.as_deref()?).ok()?;
+		strings
+			.lines()
+			.rev()
+			.filter_map(|line| {
+				let (k, rest) = quoted(line.trim_start())?;
+				let rest = rest.trim_start().strip_prefix('=')?;
+				let (value, rest) = quoted(rest.trim_start())?;
+				rest.trim_start().starts_with(';').then_some((k, value))
+			})
+			.find(|(k, _)| k == key)
+			.map(|(_, value)| value)
+	}
+
 	pub(crate) fn paths(&self) -> Vec<(String, &Vec<u8>)> {
 		let mut v = self.images.paths();
 		if let Some(strings) = &self.strings {
@@ -189,6 +517,24 @@ impl LocalizedAssets {
 	}
 }
 
+/// Unescape a leading double-quoted string, returning it with the remaining input
+fn quoted(s: &str) -> Option<(String, &str)> {
+	let mut chars = s.strip_prefix('"')?.char_indices();
+	let mut unescaped = String::new();
+	while let Some((i, c)) = chars.next() {
+		match c {
+			'"' => return Some((unescaped, &s[i + 2..])),
+			'\\' => unescaped.push(match chars.next()?.1 {
+				'n' => '\n',
+				't' => '\t',
+				c => c,
+			}),
+			c => unescaped.push(c),
+		}
+	}
+	None
+}
+
 #[derive(Debug, Clone, Default)]
 pub struct ImageAsset {
 	pub size_x1: Option<Vec<u8>>,
@@ -205,17 +551,319 @@ impl ImageAsset {
 		}
 	}
 
-	pub(crate) fn versions(&self) -> Vec<(&str, &Vec<u8>)> {
+	/// Remove a single version of the image, returning its content if it was set
+	pub fn remove(&mut self, version: &Version) -> Option<Vec<u8>> {
+		match version {
+			Version::Standard => self.size_x1.take(),
+			Version::Size2X => self.size_x2.take(),
+			Version::Size3X => self.size_x3.take(),
+		}
+	}
+
+	/// Fill the missing lower scales by downsampling the highest resolution version
+	///
+	/// Existing versions are kept as is, nothing happens without a `@2x` or `@3x` version.
+	///
+	/// # Errors
+	///
+	/// Fails if the highest resolution version is not a valid PNG image.
+	#[cfg(feature = "image")]
+	pub fn generate_scales(&mut self) -> crate::Result<()> {
+		use image::{imageops::FilterType, ImageFormat};
+
+		let (source, scale) = match (&self.size_x3, &self.size_x2) {
+			(Some(x3), _) => (x3, 3),
+			(None, Some(x2)) => (x2, 2),
+			(None, None) => return Ok(()),
+		};
+		let source = image::load_from_memory_with_format(source, ImageFormat::Png)?;
+
+		let scaled = |factor: u32| -> crate::Result<Vec<u8>> {
+			let width = (source.width() * factor / scale).max(1);
+			let height = (source.height() * factor / scale).max(1);
+			let mut png = io::Cursor::new(vec![]);
+			source
+				.resize_exact(width, height, FilterType::Lanczos3)
+				.write_to(&mut png, ImageFormat::Png)?;
+			Ok(png.into_inner())
+		};
+
+		if scale == 3 && self.size_x2.is_none() {
+			self.size_x2 = Some(scaled(2)?);
+		}
+		if self.size_x1.is_none() {
+			self.size_x1 = Some(scaled(1)?);
+		}
+
+		Ok(())
+	}
+
+	pub(crate) fn versions(&self) -> Vec<(Version, &Vec<u8>)> {
 		let mut v = vec![];
 		if let Some(s1) = &self.size_x1 {
-			v.push(("", s1));
+			v.push((Version::Standard, s1));
 		}
 		if let Some(s2) = &self.size_x2 {
-			v.push(("@2x", s2));
+			v.push((Version::Size2X, s2));
 		}
 		if let Some(s3) = &self.size_x3 {
-			v.push(("@3x", s3));
+			v.push((Version::Size3X, s3));
 		}
 		v
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn manifest_hashing() {
+		let mut manifest = Manifest::default();
+		manifest.add_file("pass.json", b"abc");
+
+		assert_eq!(
+			manifest.assets["pass.json"],
+			"a9993e364706816aba3e25717850c26c9cd0d89d"
+		);
+		assert!(manifest.verify_file("pass.json", b"abc"));
+		assert!(!manifest.verify_file("pass.json", b"abd"));
+		assert!(!manifest.verify_file("icon.png", b"abc"));
+
+		// the last content of a file added twice wins
+		manifest.add_file("pass.json", b"abd");
+		assert!(manifest.verify_file("pass.json", b"abd"));
+		assert!(!manifest.verify_file("pass.json", b"abc"));
+	}
+
+	#[test]
+	fn manifest_hash_policies() {
+		for (policy, len) in [
+			(ManifestHashPolicy::Sha1, 40),
+			(ManifestHashPolicy::Sha256, 64),
+		] {
+			let mut manifest = Manifest::new(policy);
+			manifest.add_file("pass.json", b"abc");
+
+			assert_eq!(manifest.assets["pass.json"].len(), len);
+			assert!(manifest.verify_file("pass.json", b"abc"), "{policy:?}");
+			assert!(!manifest.verify_file("pass.json", b"abd"), "{policy:?}");
+		}
+
+		// a truncated digest is a mismatch, not a panic
+		let mut manifest = Manifest::default();
+		manifest
+			.assets
+			.insert("pass.json".into(), "a9993e36".into());
+		assert!(!manifest.verify_file("pass.json", b"abc"));
+		assert!(!manifest.verify_file("icon.png", b"abc"));
+		assert_eq!(
+			ManifestHashPolicy::Sha256.digest(b"abc"),
+			"ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+		);
+	}
+
+	#[test]
+	#[cfg(all(feature = "rustcrypto", feature = "sign"))]
+	fn rustcrypto_matches_openssl() {
+		let data = b"The quick brown fox jumps over the lazy dog";
+		assert_eq!(
+			ManifestHashPolicy::Sha1.digest(data),
+			hex::encode(openssl::sha::sha1(data))
+		);
+		assert_eq!(
+			ManifestHashPolicy::Sha256.digest(data),
+			hex::encode(openssl::sha::sha256(data))
+		);
+	}
+
+	#[test]
+	fn image_path_round_trip() -> io::Result<()> {
+		for path in ["logo.png", "logo@2x.png", "strip@3x.png"] {
+			let mut images = ImageAssets::default();
+			images.get_mut(path)?.push(0);
+			assert_eq!(images.paths()[0].0, path);
+		}
+		assert!(ImageAssets::default().get_mut("logo@4x.png").is_err());
+		assert!(ImageAssets::default().get_mut("logo2x.png").is_err());
+
+		for version in [Version::Standard, Version::Size2X, Version::Size3X] {
+			assert_eq!(version.to_string().parse(), Ok(version));
+		}
+
+		Ok(())
+	}
+
+	#[test]
+	fn set_localized_images() -> Result<(), Box<dyn std::error::Error>> {
+		let mut assets = Assets::default();
+		assets.set(Image::Logo, &Version::Standard, vec![0]);
+		assets.set_localized("fr".parse()?, Image::Logo, &Version::Size2X, vec![1]);
+		assets.set_localized("en".parse()?, Image::Logo, &Version::Size2X, vec![2]);
+
+		let mut paths = assets.paths();
+		paths.sort();
+		assert_eq!(
+			paths,
+			[
+				("en.lproj/logo@2x.png".into(), &vec![2]),
+				("fr.lproj/logo@2x.png".into(), &vec![1]),
+				("logo.png".into(), &vec![0]),
+			]
+		);
+
+		Ok(())
+	}
+
+	#[test]
+	fn canonical_language_tags() -> crate::Result<()> {
+		for (tag, canonical) in [
+			("fr", "fr"),
+			("FR", "fr"),
+			("fr_FR", "fr-FR"),
+			("fr-fr", "fr-FR"),
+			(" en-GB ", "en-GB"),
+			("zh-hans", "zh-Hans"),
+			("ZH_HANT_tw", "zh-Hant-TW"),
+		] {
+			assert_eq!(Assets::parse_language(tag)?.to_string(), canonical, "{tag}");
+		}
+
+		for tag in ["", "und", "f", "fr-", "12", "fr FR"] {
+			assert!(
+				matches!(Assets::parse_language(tag), Err(crate::Error::InvalidLanguageTag(invalid)) if invalid == tag),
+				"{tag}"
+			);
+		}
+
+		let mut assets = Assets::default();
+		assets
+			.get_by_tag("fr_FR")?
+			.insert_string("Hello", "Bonjour");
+		assert_eq!(
+			assets.digests().into_keys().collect::<Vec<_>>(),
+			["fr-FR.lproj/pass.strings"]
+		);
+
+		Ok(())
+	}
+
+	#[test]
+	fn remove_assets() -> Result<(), Box<dyn std::error::Error>> {
+		let mut assets = Assets::default();
+		assets.images.logo.size_x1 = Some(vec![1]);
+		assets.images.logo.size_x3 = Some(vec![3]);
+		assets.images.icon.size_x1 = Some(vec![0]);
+
+		let fr: LanguageIdentifier = "fr".parse()?;
+		assets.get(fr.clone()).strings = Some(vec![]);
+
+		assert_eq!(assets.images.logo.remove(&Version::Size3X), Some(vec![3]));
+		assert_eq!(assets.images.logo.remove(&Version::Size3X), None);
+		assert_eq!(assets.paths().len(), 3);
+
+		let icon = assets.images.remove(Image::Icon);
+		assert_eq!(icon.size_x1, Some(vec![0]));
+		assert!(assets.remove_language(&fr).is_some());
+		assert_eq!(assets.paths().len(), 1);
+
+		assets.insert_raw("vendor/data.bin".into(), vec![4]);
+		assert_eq!(assets.raw().len(), 1);
+		assets.clear();
+		assert!(assets.paths().is_empty());
+		assert!(assets.raw().is_empty());
+		assert!(assets.is_empty());
+
+		Ok(())
+	}
+
+	#[test]
+	fn assets_content_hash() -> Result<(), Box<dyn std::error::Error>> {
+		let mut assets = Assets::default();
+		assert!(assets.is_empty());
+		let empty = assets.content_hash();
+
+		assets.set(Image::Logo, &Version::Size2X, vec![0, 1, 2]);
+		assets.get("fr".parse()?).strings = Some(b"\"Hi\" = \"Salut\";".to_vec());
+		assert!(!assets.is_empty());
+		let hash = assets.content_hash();
+		assert_ne!(hash, empty);
+
+		let mut changed = assets.clone();
+		changed.images.logo.size_x2 = Some(vec![0, 1, 3]);
+		assert_ne!(changed.content_hash(), hash);
+		assert_ne!(changed, assets);
+
+		// an empty language holds no file
+		let mut same = assets.clone();
+		same.get("de".parse()?);
+		assert_eq!(same.content_hash(), hash);
+		assert_eq!(same, assets);
+
+		Ok(())
+	}
+
+	#[test]
+	#[cfg(feature = "image")]
+	fn generate_scales_from_3x() -> crate::Result<()> {
+		use image::{ImageFormat, RgbaImage};
+
+		let png = |width, height| -> crate::Result<Vec<u8>> {
+			let mut png = io::Cursor::new(vec![]);
+			RgbaImage::new(width, height).write_to(&mut png, ImageFormat::Png)?;
+			Ok(png.into_inner())
+		};
+		let dimensions = |png: &Option<Vec<u8>>| -> crate::Result<(u32, u32)> {
+			let png = png.as_deref().unwrap_or_default();
+			let image = image::load_from_memory_with_format(png, ImageFormat::Png)?;
+			Ok((image.width(), image.height()))
+		};
+
+		let mut icon = ImageAsset {
+			size_x3: Some(png(87, 87)?),
+			..ImageAsset::default()
+		};
+		let x3 = icon.size_x3.clone();
+		icon.generate_scales()?;
+
+		assert_eq!(icon.size_x3, x3);
+		assert_eq!(dimensions(&icon.size_x2)?, (58, 58));
+		assert_eq!(dimensions(&icon.size_x1)?, (29, 29));
+
+		Ok(())
+	}
+
+	#[test]
+	fn images_map() -> crate::Result<()> {
+		let mut assets = Assets::default();
+		assets.images.logo.size_x2 = Some(b"logo@2x".to_vec());
+		assets.images.logo.size_x3 = Some(b"logo@3x".to_vec());
+		assets.images.strip.size_x1 = Some(b"strip".to_vec());
+		assets.images.strip.size_x2 = Some(b"strip@2x".to_vec());
+		assets.get_by_tag("fr")?.images.icon.size_x1 = Some(b"icon".to_vec());
+
+		assert_eq!(
+			assets.images_map(),
+			BTreeMap::from([
+				(Image::Logo, &b"logo@2x"[..]),
+				(Image::Strip, &b"strip"[..]),
+			])
+		);
+
+		Ok(())
+	}
+
+	#[test]
+	fn pass_strings() {
+		let mut assets = LocalizedAssets {
+			strings: Some(b"/* comment */\n\"greeting\" = \"Hello\";".to_vec()),
+			..LocalizedAssets::default()
+		};
+		assets.insert_string("quote", "say \"hi\"\n");
+		assets.insert_string("greeting", "Bonjour");
+
+		assert_eq!(assets.translate("quote").as_deref(), Some("say \"hi\"\n"));
+		assert_eq!(assets.translate("greeting").as_deref(), Some("Bonjour"));
+		assert_eq!(assets.translate("missing"), None);
+	}
+}
@@ -6,17 +6,22 @@ use std::{
 	str::FromStr,
 };
 use url::Url;
+use uuid::Uuid;
 use yansi::Painted;
 
 // TODO: make a prelude
 
+mod boarding;
 mod fields;
 mod impls;
 mod manifest;
-mod semantics;
+pub(crate) mod semantics;
+mod semantics_builder;
+pub use boarding::*;
 pub use fields::*;
 pub use manifest::*;
 pub use semantics::*;
+pub use semantics_builder::*;
 
 /// Represent the `pass.json` file content
 ///
@@ -94,6 +99,14 @@ pub struct Metadata {
 	#[serde(default, skip_serializing_if = "Vec::is_empty")]
 	pub barcodes: Vec<Barcode>,
 
+	/// The single barcode of iOS 8 and earlier, ignored by later versions when
+	/// `barcodes` is set. [`Metadata::barcodes`] falls back on it.
+	///
+	/// [`Pass::read`](crate::Pass::read) moves it to `barcodes`.
+	#[deprecated = "replaced by `barcodes` since iOS 9"]
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub barcode: Option<Barcode>,
+
 	/// An array of objects that represents the identity of Bluetooth Low Energy
 	/// beacons the system uses to show a relevant pass.
 	#[serde(default, skip_serializing_if = "Vec::is_empty")]
@@ -131,6 +144,12 @@ pub struct Metadata {
 	#[serde(skip_serializing_if = "Option::is_none")]
 	pub nfc: Option<Nfc>,
 
+	/// The styles to display the pass with, by order of preference, such as the
+	/// poster layout of event tickets introduced in iOS 18. Systems not
+	/// supporting any of them fall back to the style key of the pass.
+	#[serde(default, skip_serializing_if = "Vec::is_empty")]
+	pub preferred_style_schemes: Vec<StyleScheme>,
+
 	/// The date and time when the pass becomes relevant as a W3C timestamp, such as the start time of a movie. The value must be a complete date that includes hours and minutes, and may optionally include seconds.
 	///
 	/// For information about the W3C timestamp format, see Time and Date Formats on the W3C website.
@@ -170,7 +189,7 @@ pub struct Metadata {
 	pub authentication_token: Option<String>,
 }
 
-#[derive(Clone)]
+#[derive(Clone, PartialEq, Eq)]
 pub struct RgbColor(pub u8, pub u8, pub u8);
 
 impl RgbColor {
@@ -183,6 +202,48 @@ impl RgbColor {
 	pub const fn black() -> Self {
 		Self(0, 0, 0)
 	}
+
+	/// Relative luminance as defined by WCAG, from 0 (black) to 1 (white)
+	///
+	/// <https://www.w3.org/TR/WCAG21/#dfn-relative-luminance>
+	#[must_use]
+	pub fn relative_luminance(&self) -> f64 {
+		let channel = |c: u8| {
+			let c = f64::from(c) / 255.;
+			if c <= 0.039_28 {
+				c / 12.92
+			} else {
+				((c + 0.055) / 1.055).powf(2.4)
+			}
+		};
+
+		0.0722f64.mul_add(
+			channel(self.2),
+			0.2126f64.mul_add(channel(self.0), 0.7152 * channel(self.1)),
+		)
+	}
+
+	/// Contrast ratio between two colors as defined by WCAG, from 1:1 to 21:1
+	///
+	/// <https://www.w3.org/TR/WCAG21/#dfn-contrast-ratio>
+	#[must_use]
+	pub fn contrast_ratio(&self, other: &Self) -> f64 {
+		let (a, b) = (self.relative_luminance(), other.relative_luminance());
+		let (lighter, darker) = if a > b { (a, b) } else { (b, a) };
+		(lighter + 0.05) / (darker + 0.05)
+	}
+}
+
+impl From<(u8, u8, u8)> for RgbColor {
+	fn from((red, green, blue): (u8, u8, u8)) -> Self {
+		Self(red, green, blue)
+	}
+}
+
+impl From<[u8; 3]> for RgbColor {
+	fn from([red, green, blue]: [u8; 3]) -> Self {
+		Self(red, green, blue)
+	}
 }
 
 impl fmt::Debug for RgbColor {
@@ -204,76 +265,112 @@ impl Serialize for RgbColor {
 }
 
 impl FromStr for RgbColor {
-	type Err = &'static str;
+	type Err = crate::Error;
 
 	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		let invalid = |reason: String| crate::Error::InvalidColor {
+			color: s.into(),
+			reason,
+		};
+
 		match s.get(0..=0) {
 			Some("r") => {
 				// TODO: proper string parsing
-				let s = s
+				let mut components = s
 					.strip_prefix("rgb(")
 					.and_then(|s| s.strip_suffix(')'))
-					.map(|s| s.split(','))
-					.ok_or("could not split on `,`")?;
-
-				let mut vec = s.map(str::trim).map(str::parse);
-				// TODO: error handling
-				let red = vec
-					.next()
-					.ok_or("no red color")?
-					.map_err(|_| "could not parse red color")?;
-				let green = vec
-					.next()
-					.ok_or("no green color")?
-					.map_err(|_| "could not parse green color")?;
-				let blue = vec
-					.next()
-					.ok_or("no blue color")?
-					.map_err(|_| "could not parse blue color")?;
-				if vec.next().is_some() {
-					return Err("rgb only has 3 colors");
+					.map(|s| s.split(',').map(str::trim))
+					.ok_or_else(|| invalid("expected `rgb(red, green, blue)`".into()))?;
+
+				let mut component = |channel: &str| {
+					let component = components
+						.next()
+						.ok_or_else(|| invalid(format!("no {channel} component")))?;
+					component.parse::<u8>().map_err(|_| {
+						invalid(if component.parse::<i64>().is_ok() {
+							format!("{channel} component {component} out of range 0-255")
+						} else {
+							format!("{channel} component `{component}` is not a number")
+						})
+					})
 				};
 
+				let red = component("red")?;
+				let green = component("green")?;
+				let blue = component("blue")?;
+				if components.next().is_some() {
+					return Err(invalid("rgb only has 3 components".into()));
+				}
+
 				Ok(Self(red, green, blue))
 			}
 			// TODO: remove, move to future fault tolerant pkpass parser
 			// custom deser for color found in mcdonalds pkpass
 			Some("#") => {
-				let s = s.strip_prefix('#').unwrap();
-
-				let red = s
-					.get(0..=1)
-					.map(|by| hex::decode(by).unwrap()[0])
-					.map(u8::try_from)
-					.unwrap()
-					.unwrap();
-				let green = s
-					.get(2..=3)
-					.map(|by| hex::decode(by).unwrap()[0])
-					.map(u8::try_from)
-					.unwrap()
-					.unwrap();
-				let blue = s
-					.get(4..=5)
-					.map(|by| hex::decode(by).unwrap()[0])
-					.map(u8::try_from)
-					.unwrap()
-					.unwrap();
-				let rest = s.get(6..).unwrap();
-				assert!(rest.is_empty());
-
-				Ok(Self(red, green, blue))
+				let s = s.strip_prefix('#').unwrap_or(s);
+				let bytes = hex::decode(s)
+					.map_err(|err| invalid(format!("could not parse hex color: {err}")))?;
+
+				match bytes[..] {
+					[red, green, blue] => Ok(Self(red, green, blue)),
+					_ => Err(invalid("hex color only has 3 components".into())),
+				}
 			}
-			_ => Err("format not recognized"),
+			_ => Err(invalid(
+				"expected `rgb(red, green, blue)` or `#rrggbb`".into(),
+			)),
 		}
 	}
 }
 
+impl TryFrom<&str> for RgbColor {
+	type Error = crate::Error;
+
+	fn try_from(value: &str) -> Result<Self, Self::Error> {
+		value.parse()
+	}
+}
+
 impl<'de> Deserialize<'de> for RgbColor {
 	fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
 		let s = String::deserialize(deserializer)?;
-		s.parse()
-			.map_err(|msg| serde::de::Error::custom(format!("could not parse color: {msg}")))
+		s.parse().map_err(serde::de::Error::custom)
+	}
+}
+
+/// A string displayed on the pass, translated through `pass.strings`
+///
+/// Wallet looks the string up as a key in the `pass.strings` file of the
+/// device language and displays it verbatim when there is no translation.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct LocalizableString(pub String);
+
+impl LocalizableString {
+	/// The string as displayed for a language, falling back to the key itself
+	#[must_use]
+	pub fn localize(&self, assets: Option<&LocalizedAssets>) -> String {
+		assets
+			.and_then(|assets| assets.translate(&self.0))
+			.unwrap_or_else(|| self.0.clone())
+	}
+}
+
+impl From<&str> for LocalizableString {
+	fn from(key: &str) -> Self {
+		Self(key.into())
+	}
+}
+
+impl From<String> for LocalizableString {
+	fn from(key: String) -> Self {
+		Self(key)
+	}
+}
+
+impl fmt::Display for LocalizableString {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.write_str(&self.0)
 	}
 }
 
@@ -298,7 +395,17 @@ pub struct Barcode {
 	///
 	/// The alternative text isn’t displayed for watchOS.
 	#[serde(skip_serializing_if = "Option::is_none")]
-	pub alt_text: Option<String>,
+	pub alt_text: Option<LocalizableString>,
+}
+
+/// <https://developer.apple.com/documentation/walletpasses/pass/preferredstyleschemes>
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum StyleScheme {
+	/// The poster layout, showing the `artwork` image full bleed
+	PosterEventTicket,
+	/// The original event ticket layout
+	EventTicket,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -314,6 +421,13 @@ pub enum BarcodeFormat {
 	Pdf128,
 }
 
+/// A device displaying passes, to tell which barcode it shows, see [`Metadata::barcode_for`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Device {
+	Iphone,
+	AppleWatch,
+}
+
 /// <https://developer.apple.com/documentation/walletpasses/pass/beacons>
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase", deny_unknown_fields)]
@@ -328,7 +442,7 @@ pub struct Beacon {
 
 	// The unique identifier of a Bluetooth Low Energy location beacon.
 	#[serde(rename = "proximityUUID")]
-	proximity_uuid: String,
+	proximity_uuid: Uuid,
 
 	// The text to display on the lock screen when the pass is relevant. For
 	/// example, a description of a nearby location, such as “Store nearby on
@@ -337,6 +451,15 @@ pub struct Beacon {
 	relevant_text: Option<String>,
 }
 
+impl Beacon {
+	/// Whether both beacons identify the same region
+	pub(crate) fn same_region(&self, other: &Self) -> bool {
+		self.proximity_uuid == other.proximity_uuid
+			&& self.major == other.major
+			&& self.minor == other.minor
+	}
+}
+
 /// <https://developer.apple.com/documentation/walletpasses/pass/locations>
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase", deny_unknown_fields)]
@@ -380,3 +503,437 @@ pub struct Nfc {
 	#[serde(skip_serializing_if = "Option::is_none")]
 	pub requires_authentication: Option<bool>,
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{
+		testing::{config, minimal_pass_json},
+		PassConfig,
+	};
+
+	#[test]
+	fn rgb_color_conversions() -> Result<(), Box<dyn std::error::Error>> {
+		let from_tuple = RgbColor::from((23, 187, 82));
+		let from_array = RgbColor::from([23, 187, 82]);
+		assert_eq!(from_tuple, from_array);
+
+		assert_eq!(serde_json::to_string(&from_tuple)?, r#""rgb(23,187,82)""#);
+		assert_eq!(serde_json::to_string(&from_array)?, r#""rgb(23,187,82)""#);
+
+		Ok(())
+	}
+
+	#[test]
+	fn rgb_color_errors() {
+		let reason = |color: &str| match color.parse::<RgbColor>() {
+			Err(crate::Error::InvalidColor { reason, .. }) => reason,
+			other => panic!("{color}: {other:?}"),
+		};
+
+		assert_eq!(
+			reason("rgb(300, 0, 0)"),
+			"red component 300 out of range 0-255"
+		);
+		assert_eq!(
+			reason("rgb(0, -1, 0)"),
+			"green component -1 out of range 0-255"
+		);
+		assert_eq!(
+			reason("rgb(0, 0, blue)"),
+			"blue component `blue` is not a number"
+		);
+		assert_eq!(reason("rgb(0, 0)"), "no blue component");
+		assert_eq!(reason("rgb(0, 0, 0, 0)"), "rgb only has 3 components");
+		assert_eq!(
+			reason("blue"),
+			"expected `rgb(red, green, blue)` or `#rrggbb`"
+		);
+	}
+
+	#[test]
+	fn pdf417_binary_round_trip() -> Result<(), Box<dyn std::error::Error>> {
+		let bytes = (0..=255).collect::<Vec<u8>>();
+		let barcode = Barcode::pdf417_binary(&bytes);
+		assert_eq!(barcode.message.chars().count(), 256);
+
+		let json = serde_json::to_string(&barcode)?;
+		let read: Barcode = serde_json::from_str(&json)?;
+		assert_eq!(read.message_encoding, "iso-8859-1");
+		assert_eq!(read.latin1_bytes(), Some(bytes));
+
+		let utf8 = Barcode {
+			message_encoding: "utf-8".into(),
+			..read.clone()
+		};
+		assert_eq!(utf8.latin1_bytes(), None);
+		let not_latin1 = Barcode {
+			message: "€".into(),
+			..read
+		};
+		assert_eq!(not_latin1.latin1_bytes(), None);
+
+		Ok(())
+	}
+
+	#[test]
+	fn primary_barcode() {
+		let mut metadata = Metadata::new(config());
+		assert!(metadata.primary_barcode().is_none());
+
+		metadata.barcodes.push(Barcode::pdf417_binary(b"1234"));
+		metadata.set_primary_barcode(Barcode {
+			format: BarcodeFormat::Qr,
+			message: "1234".into(),
+			message_encoding: "iso-8859-1".into(),
+			alt_text: None,
+		});
+
+		assert!(matches!(
+			metadata.primary_barcode(),
+			Some(Barcode {
+				format: BarcodeFormat::Qr,
+				..
+			})
+		));
+		assert_eq!(metadata.barcodes.len(), 2);
+	}
+
+	#[test]
+	fn nfc_message_length() -> Result<(), Box<dyn std::error::Error>> {
+		let mut nfc = Nfc::new("MDkwEwYHKoZIzj0CAQYIKoZIzj0DAQcDIgAC", "a".repeat(64))?;
+
+		// 22 characters but 66 bytes
+		let message = "€".repeat(22);
+		assert!(matches!(
+			nfc.set_message(message.clone()),
+			Err(crate::Error::NfcMessageTooLong(66))
+		));
+		assert_eq!(nfc.message, "a".repeat(64));
+		assert!(Nfc::new("", message).is_err());
+
+		Ok(())
+	}
+
+	#[test]
+	fn empty_fields() -> Result<(), Box<dyn std::error::Error>> {
+		let mut metadata = Metadata::new(PassConfig {
+			kind: PassKind::EventTicket(Fields::default()),
+			..config()
+		});
+		assert!(metadata
+			.kind_fields_mut()
+			.is_some_and(|fields| fields.is_empty()));
+
+		// the key is kept as it sets the style of the pass
+		let pass_json = serde_json::to_value(&metadata)?;
+		assert_eq!(pass_json["eventTicket"], serde_json::json!({}));
+		let read: Metadata = serde_json::from_value(pass_json)?;
+		assert_eq!(read.kinds(), ["eventTicket"]);
+
+		Ok(())
+	}
+
+	#[test]
+	fn store_card_balance() -> Result<(), Box<dyn std::error::Error>> {
+		let mut metadata = Metadata::new(PassConfig {
+			kind: PassKind::StoreCard(Fields::default()),
+			..config()
+		});
+
+		metadata.set_store_card_balance("12.50", "eur", Some("balance"))?;
+		metadata.set_store_card_balance("7.25", "EUR", Some("balance"))?;
+
+		let balance = metadata
+			.semantics
+			.as_ref()
+			.and_then(|tags| tags.balance.as_ref());
+		assert_eq!(balance.and_then(|b| b.amount.as_deref()), Some("7.25"));
+		assert_eq!(
+			balance.and_then(|b| b.currency_code.as_deref()),
+			Some("EUR")
+		);
+		let fields = metadata.fields().collect::<Vec<_>>();
+		assert_eq!(fields.len(), 1);
+		assert_eq!(fields[0].value, "7.25");
+		assert_eq!(fields[0].currency_code.as_deref(), Some("EUR"));
+
+		assert!(matches!(
+			metadata.set_store_card_balance("1", "EURO", None),
+			Err(crate::Error::InvalidCurrencyCode(code)) if code == "EURO"
+		));
+
+		let mut coupon = Metadata::new(PassConfig {
+			kind: PassKind::Coupon(Fields::default()),
+			..config()
+		});
+		assert!(matches!(
+			coupon.set_store_card_balance("1", "EUR", None),
+			Err(crate::Error::SemanticTagNotApplicable {
+				key: "balance",
+				pass: "coupon"
+			})
+		));
+
+		Ok(())
+	}
+
+	#[test]
+	fn validated_locations() -> Result<(), Box<dyn std::error::Error>> {
+		let mut metadata = Metadata::new(PassConfig {
+			kind: PassKind::StoreCard(Fields::default()),
+			..config()
+		});
+
+		let store = Location::new(-33.8688, 151.2093)?.relevant_text("Store nearby");
+		assert_eq!(store.relevant_text.as_deref(), Some("Store nearby"));
+		for _ in 0..Metadata::MAX_LOCATIONS {
+			metadata.add_location(store.clone())?;
+		}
+		assert!(matches!(
+			metadata.add_location(store),
+			Err(crate::Error::TooManyLocations)
+		));
+		assert_eq!(metadata.locations.len(), 10);
+
+		assert!(matches!(
+			Location::new(91.0, 0.0),
+			Err(crate::Error::InvalidCoordinate {
+				axis: "latitude",
+				..
+			})
+		));
+		assert!(matches!(
+			Location::new(0.0, -180.5),
+			Err(crate::Error::InvalidCoordinate {
+				axis: "longitude",
+				..
+			})
+		));
+		assert!(Location::new(f64::NAN, 0.0).is_err());
+
+		Ok(())
+	}
+
+	#[test]
+	fn barcode_for_device() {
+		let mut metadata = Metadata::new(config());
+		assert!(metadata.barcode_for(Device::Iphone).is_none());
+
+		let barcode = |format| Barcode {
+			format,
+			message: "1234".into(),
+			message_encoding: "iso-8859-1".into(),
+			alt_text: None,
+		};
+		metadata.barcodes = vec![barcode(BarcodeFormat::Pdf128), barcode(BarcodeFormat::Qr)];
+
+		assert_eq!(metadata.barcodes().len(), 2);
+		assert!(matches!(
+			metadata.barcode_for(Device::Iphone),
+			Some(Barcode {
+				format: BarcodeFormat::Pdf128,
+				..
+			})
+		));
+		assert!(matches!(
+			metadata.barcode_for(Device::AppleWatch),
+			Some(Barcode {
+				format: BarcodeFormat::Qr,
+				..
+			})
+		));
+
+		metadata.barcodes.truncate(1);
+		assert!(metadata.barcode_for(Device::AppleWatch).is_none());
+	}
+
+	#[test]
+	#[allow(deprecated)]
+	fn legacy_single_barcode() -> Result<(), Box<dyn std::error::Error>> {
+		let mut pass_json = minimal_pass_json();
+		pass_json["generic"] = serde_json::json!({});
+		pass_json["barcode"] = serde_json::json!({
+			"format": "PKBarcodeFormatQR",
+			"message": "1234",
+			"messageEncoding": "iso-8859-1",
+		});
+		let metadata: Metadata = serde_json::from_value(pass_json.clone())?;
+		assert!(metadata.barcode.is_some());
+		assert!(metadata.barcodes.is_empty());
+		assert_eq!(metadata.barcodes().len(), 1);
+		assert_eq!(
+			metadata.primary_barcode().map(|b| b.message.as_str()),
+			Some("1234")
+		);
+		assert!(serde_json::to_value(&metadata)?.get("barcode").is_some());
+
+		// later versions ignore it when `barcodes` is set
+		pass_json["barcodes"] = serde_json::json!([{
+			"format": "PKBarcodeFormatAztec",
+			"message": "5678",
+			"messageEncoding": "iso-8859-1",
+		}]);
+		let metadata: Metadata = serde_json::from_value(pass_json)?;
+		assert_eq!(
+			metadata.primary_barcode().map(|b| b.message.as_str()),
+			Some("5678")
+		);
+
+		Ok(())
+	}
+
+	#[test]
+	fn metadata_to_config() -> Result<(), Box<dyn std::error::Error>> {
+		let fields = Fields {
+			primary: vec![Field::new("balance", "21.00")],
+			..Fields::default()
+		};
+		let metadata = Metadata::new(PassConfig {
+			kind: PassKind::StoreCard(fields),
+			..config()
+		});
+
+		let config = metadata.to_config();
+		assert_eq!(config.organization_name, "Acme Inc.");
+		assert_eq!(config.description, "A test pass");
+		assert_eq!(config.serial_number, "1234");
+		let PassKind::StoreCard(fields) = &config.kind else {
+			panic!("expected a store card, got {:?}", config.kind);
+		};
+		assert_eq!(fields.iter().count(), 1);
+
+		// no style, as deserialized metadata allows
+		let metadata: Metadata = serde_json::from_value(minimal_pass_json())?;
+		assert!(matches!(metadata.kind(), PassKind::Generic(fields) if fields.is_empty()));
+
+		Ok(())
+	}
+
+	#[test]
+	fn read_minimal_pass_json() -> Result<(), Box<dyn std::error::Error>> {
+		let mut pass_json = minimal_pass_json();
+		pass_json["generic"] = serde_json::json!({});
+		let metadata: Metadata = serde_json::from_value(pass_json)?;
+
+		assert_eq!(metadata.serial_number, "1234");
+		assert!(metadata.generic.is_some());
+		assert!(metadata.associated_store_identifiers.is_empty());
+		assert!(metadata.barcodes.is_empty());
+		assert!(metadata.beacons.is_empty());
+		assert!(metadata.locations.is_empty());
+
+		Ok(())
+	}
+
+	#[test]
+	fn generic_is_the_default_kind() {
+		assert!(matches!(PassKind::default(), PassKind::Generic(_)));
+
+		let kind = PassKind::from(Fields {
+			primary: vec![Field::new("name", "Jane Doe")],
+			..Fields::default()
+		});
+		assert!(matches!(kind, PassKind::Generic(fields) if fields.primary.len() == 1));
+	}
+
+	#[test]
+	fn beacon_proximity_uuid() -> Result<(), Box<dyn std::error::Error>> {
+		let beacon: Beacon = serde_json::from_str(
+			r#"{ "proximityUUID": "E2C56DB5-DFFB-48D2-B060-D0F5A71096E0", "major": 1 }"#,
+		)?;
+		assert_eq!(
+			beacon.proximity_uuid,
+			Uuid::parse_str("e2c56db5-dffb-48d2-b060-d0f5a71096e0")?
+		);
+		assert_eq!(
+			serde_json::to_string(&beacon)?,
+			r#"{"major":1,"proximityUUID":"e2c56db5-dffb-48d2-b060-d0f5a71096e0"}"#
+		);
+
+		for malformed in ["", "not-a-uuid", "e2c56db5-dffb-48d2-b060"] {
+			let json = serde_json::json!({ "proximityUUID": malformed });
+			assert!(
+				serde_json::from_value::<Beacon>(json).is_err(),
+				"{malformed}"
+			);
+		}
+
+		Ok(())
+	}
+
+	#[test]
+	fn reject_malformed_dates() -> Result<(), Box<dyn std::error::Error>> {
+		let mut pass_json = minimal_pass_json();
+		pass_json["generic"] = serde_json::json!({});
+		pass_json["expirationDate"] = "2024-07-22T18:30:00+02:00".into();
+		pass_json["relevantDate"] = "2024-07-22T16:30:00Z".into();
+		let metadata: Metadata = serde_json::from_value(pass_json.clone())?;
+		assert_eq!(metadata.expiration_date, metadata.relevant_date);
+
+		for key in ["expirationDate", "relevantDate"] {
+			let mut malformed = pass_json.clone();
+			malformed[key] = "not-a-date".into();
+			assert!(serde_json::from_value::<Metadata>(malformed).is_err());
+		}
+		pass_json["expirationDate"] = serde_json::Value::Null;
+		assert!(serde_json::from_value::<Metadata>(pass_json)?
+			.expiration_date
+			.is_none());
+
+		Ok(())
+	}
+
+	#[test]
+	fn replace_in_every_field() {
+		let region = |name: &str| vec![Field::new(name, format!("{name} for {{{{name}}}}"))];
+		let mut metadata = Metadata::new(PassConfig {
+			kind: PassKind::StoreCard(Fields {
+				header: region("header"),
+				primary: region("primary"),
+				secondary: region("secondary"),
+				auxiliary: region("auxiliary"),
+				back: region("back"),
+				..Fields::default()
+			}),
+			..config()
+		});
+
+		for field in metadata.fields_mut() {
+			field.value = field.value.replace("{{name}}", "Jane");
+		}
+
+		let values = metadata
+			.fields_mut()
+			.map(|field| field.value.clone())
+			.collect::<Vec<_>>();
+		assert_eq!(
+			values,
+			[
+				"header for Jane",
+				"primary for Jane",
+				"secondary for Jane",
+				"auxiliary for Jane",
+				"back for Jane"
+			]
+		);
+	}
+
+	#[test]
+	fn kind_fields_of_any_style() {
+		for kind in [
+			PassKind::EventTicket(Fields::default()),
+			PassKind::StoreCard(Fields::default()),
+		] {
+			let mut metadata = Metadata::new(PassConfig { kind, ..config() });
+
+			let fields = metadata.kind_fields_mut().expect("a style is set");
+			fields.primary.push(Field::new("name", "Jane"));
+			assert_eq!(metadata.fields_mut().count(), 1);
+		}
+
+		let mut metadata: Metadata =
+			serde_json::from_value(minimal_pass_json()).expect("minimal pass.json");
+		assert!(metadata.kind_fields_mut().is_none());
+	}
+}
@@ -1,6 +1,52 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
+/// Two uppercase letters or digits, such as “EX” or “U2”
+pub(crate) fn is_airline_code(code: &str) -> bool {
+	code.len() == 2
+		&& code
+			.bytes()
+			.all(|b| b.is_ascii_uppercase() || b.is_ascii_digit())
+}
+
+/// Three uppercase letters, such as “LHR”
+pub(crate) fn is_airport_code(code: &str) -> bool {
+	code.len() == 3 && code.bytes().all(|b| b.is_ascii_uppercase())
+}
+
+/// An airline code followed by one to four digits, such as “EX123”
+pub(crate) fn is_flight_code(code: &str) -> bool {
+	code.get(..2).is_some_and(is_airline_code)
+		&& code.get(2..).is_some_and(|number| {
+			(1..=4).contains(&number.len()) && number.bytes().all(|b| b.is_ascii_digit())
+		})
+}
+
+/// Active ISO 4217 currency codes, sorted
+///
+/// <https://www.iso.org/iso-4217-currency-codes.html>
+const CURRENCY_CODES: &[&str] = &[
+	"AED", "AFN", "ALL", "AMD", "ANG", "AOA", "ARS", "AUD", "AWG", "AZN", "BAM", "BBD", "BDT",
+	"BGN", "BHD", "BIF", "BMD", "BND", "BOB", "BOV", "BRL", "BSD", "BTN", "BWP", "BYN", "BZD",
+	"CAD", "CDF", "CHE", "CHF", "CHW", "CLF", "CLP", "CNY", "COP", "COU", "CRC", "CUC", "CUP",
+	"CVE", "CZK", "DJF", "DKK", "DOP", "DZD", "EGP", "ERN", "ETB", "EUR", "FJD", "FKP", "GBP",
+	"GEL", "GHS", "GIP", "GMD", "GNF", "GTQ", "GYD", "HKD", "HNL", "HTG", "HUF", "IDR", "ILS",
+	"INR", "IQD", "IRR", "ISK", "JMD", "JOD", "JPY", "KES", "KGS", "KHR", "KMF", "KPW", "KRW",
+	"KWD", "KYD", "KZT", "LAK", "LBP", "LKR", "LRD", "LSL", "LYD", "MAD", "MDL", "MGA", "MKD",
+	"MMK", "MNT", "MOP", "MRU", "MUR", "MVR", "MWK", "MXN", "MXV", "MYR", "MZN", "NAD", "NGN",
+	"NIO", "NOK", "NPR", "NZD", "OMR", "PAB", "PEN", "PGK", "PHP", "PKR", "PLN", "PYG", "QAR",
+	"RON", "RSD", "RUB", "RWF", "SAR", "SBD", "SCR", "SDG", "SEK", "SGD", "SHP", "SLE", "SLL",
+	"SOS", "SRD", "SSP", "STN", "SVC", "SYP", "SZL", "THB", "TJS", "TMT", "TND", "TOP", "TRY",
+	"TTD", "TWD", "TZS", "UAH", "UGX", "USD", "USN", "UYI", "UYU", "UYW", "UZS", "VED", "VES",
+	"VND", "VUV", "WST", "XAF", "XAG", "XAU", "XBA", "XBB", "XBC", "XBD", "XCD", "XCG", "XDR",
+	"XOF", "XPD", "XPF", "XPT", "XSU", "XTS", "XUA", "XXX", "YER", "ZAR", "ZMW", "ZWG", "ZWL",
+];
+
+/// An active ISO 4217 currency code, such as “EUR”
+pub(crate) fn is_currency_code(code: &str) -> bool {
+	CURRENCY_CODES.binary_search(&code).is_ok()
+}
+
 // TODO: replace with a map, huge empty space is taken when only a few will be registered
 /// <https://developer.apple.com/documentation/walletpasses/pass/semantictags>
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
@@ -0,0 +1,402 @@
+use crate::{
+	models::{
+		Location, SemanticEventType, SemanticTagCurrencyAmount, SemanticTagLocation,
+		SemanticTagPersonNameComponents, SemanticTagSeat, SemanticTags,
+	},
+	Error, Result,
+};
+use chrono::{DateTime, Utc};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Preset {
+	BoardingPass,
+	EventTicket,
+}
+
+impl Preset {
+	const fn name(self) -> &'static str {
+		match self {
+			Self::BoardingPass => "boarding pass",
+			Self::EventTicket => "event ticket",
+		}
+	}
+}
+
+/// Chainable setters for the most common semantic tags of a pass type
+///
+/// Tags meant for another pass type and invalid IATA codes are reported by [`SemanticTagsBuilder::build`].
+///
+/// ```
+/// use chrono::{TimeZone, Utc};
+/// use pkpass::models::SemanticTags;
+///
+/// # fn main() -> pkpass::Result<()> {
+/// let semantics = SemanticTags::boarding_pass()
+///     .flight_code("EX123")
+///     .departure_airport_code("LHR")
+///     .departure_date(Utc.with_ymd_and_hms(2024, 7, 22, 18, 30, 0).unwrap())
+///     .destination_airport_code("MPM")
+///     .build()?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct SemanticTagsBuilder {
+	preset: Preset,
+	tags: SemanticTags,
+	error: Option<Error>,
+}
+
+impl SemanticTags {
+	/// Start the semantic tags of a boarding pass
+	#[must_use]
+	pub fn boarding_pass() -> SemanticTagsBuilder {
+		SemanticTagsBuilder::new(Preset::BoardingPass)
+	}
+
+	/// Start the semantic tags of an event ticket
+	#[must_use]
+	pub fn event_ticket() -> SemanticTagsBuilder {
+		SemanticTagsBuilder::new(Preset::EventTicket)
+	}
+}
+
+impl SemanticTagsBuilder {
+	fn new(preset: Preset) -> Self {
+		Self {
+			preset,
+			tags: SemanticTags::default(),
+			error: None,
+		}
+	}
+
+	/// Apply `set` if the tag is meant for the pass type, remember the first failure otherwise
+	fn set(
+		mut self,
+		key: &'static str,
+		preset: Option<Preset>,
+		set: impl FnOnce(&mut SemanticTags) -> Result<()>,
+	) -> Self {
+		if self.error.is_some() {
+			return self;
+		}
+		if preset.is_some_and(|preset| preset != self.preset) {
+			self.error = Some(Error::SemanticTagNotApplicable {
+				key,
+				pass: self.preset.name(),
+			});
+			return self;
+		}
+		self.error = set(&mut self.tags).err();
+		self
+	}
+
+	/// Produce the semantic tags to set on the pass metadata
+	///
+	/// # Errors
+	///
+	/// Fails with the first tag not meant for the pass type or the first invalid value.
+	pub fn build(self) -> Result<SemanticTags> {
+		match self.error {
+			Some(error) => Err(error),
+			None => Ok(self.tags),
+		}
+	}
+
+	// --- any pass type
+
+	#[must_use]
+	pub fn confirmation_number(self, number: impl Into<String>) -> Self {
+		self.set("confirmationNumber", None, |tags| {
+			tags.confirmation_number = Some(number.into());
+			Ok(())
+		})
+	}
+
+	#[must_use]
+	pub fn seat(self, seat: SemanticTagSeat) -> Self {
+		self.set("seats", None, |tags| {
+			tags.add_seat(seat);
+			Ok(())
+		})
+	}
+
+	/// Total price of the pass, such as `("42.00", "EUR")`
+	#[must_use]
+	pub fn total_price(self, amount: impl Into<String>, currency_code: impl Into<String>) -> Self {
+		self.set("totalPrice", None, |tags| {
+			tags.total_price = Some(SemanticTagCurrencyAmount {
+				amount: Some(amount.into()),
+				currency_code: Some(currency_code.into()),
+			});
+			Ok(())
+		})
+	}
+
+	// --- boarding passes
+
+	/// IATA flight code, such as “EX123”, also setting the airline code and flight number
+	#[must_use]
+	pub fn flight_code(self, code: &str) -> Self {
+		self.set("flightCode", Some(Preset::BoardingPass), |tags| {
+			tags.set_flight_code(code)?;
+			let flight_code = tags.flight_code.clone().unwrap_or_default();
+			let (airline_code, flight_number) = flight_code.split_at(2);
+			tags.set_airline_code(airline_code)?;
+			tags.flight_number = flight_number.parse().ok();
+			Ok(())
+		})
+	}
+
+	#[must_use]
+	pub fn departure_airport_code(self, code: &str) -> Self {
+		self.set("departureAirportCode", Some(Preset::BoardingPass), |tags| {
+			tags.set_departure_airport_code(code)
+		})
+	}
+
+	#[must_use]
+	pub fn destination_airport_code(self, code: &str) -> Self {
+		self.set(
+			"destinationAirportCode",
+			Some(Preset::BoardingPass),
+			|tags| tags.set_destination_airport_code(code),
+		)
+	}
+
+	#[must_use]
+	pub fn departure_location(self, latitude: f64, longitude: f64) -> Self {
+		self.set("departureLocation", Some(Preset::BoardingPass), |tags| {
+			tags.departure_location = Some(SemanticTagLocation {
+				latitude,
+				longitude,
+			});
+			Ok(())
+		})
+	}
+
+	/// Same coordinates as one of the relevant locations of the pass
+	#[must_use]
+	pub fn departure_location_from(self, location: &Location) -> Self {
+		self.departure_location(location.latitude, location.longitude)
+	}
+
+	#[must_use]
+	pub fn destination_location(self, latitude: f64, longitude: f64) -> Self {
+		self.set("destinationLocation", Some(Preset::BoardingPass), |tags| {
+			tags.destination_location = Some(SemanticTagLocation {
+				latitude,
+				longitude,
+			});
+			Ok(())
+		})
+	}
+
+	/// Same coordinates as one of the relevant locations of the pass
+	#[must_use]
+	pub fn destination_location_from(self, location: &Location) -> Self {
+		self.destination_location(location.latitude, location.longitude)
+	}
+
+	/// Scheduled departure date
+	#[must_use]
+	pub fn departure_date(self, date: DateTime<Utc>) -> Self {
+		self.set(
+			"originalDepartureDate",
+			Some(Preset::BoardingPass),
+			|tags| {
+				tags.original_departure_date = Some(date);
+				Ok(())
+			},
+		)
+	}
+
+	/// Scheduled arrival date
+	#[must_use]
+	pub fn arrival_date(self, date: DateTime<Utc>) -> Self {
+		self.set("originalArrivalDate", Some(Preset::BoardingPass), |tags| {
+			tags.original_arrival_date = Some(date);
+			Ok(())
+		})
+	}
+
+	#[must_use]
+	pub fn departure_gate(self, gate: impl Into<String>) -> Self {
+		self.set("departureGate", Some(Preset::BoardingPass), |tags| {
+			tags.departure_gate = Some(gate.into());
+			Ok(())
+		})
+	}
+
+	#[must_use]
+	pub fn passenger_name(
+		self,
+		given_name: impl Into<String>,
+		family_name: impl Into<String>,
+	) -> Self {
+		self.set("passengerName", Some(Preset::BoardingPass), |tags| {
+			tags.passenger_name = Some(SemanticTagPersonNameComponents {
+				given_name: Some(given_name.into()),
+				family_name: Some(family_name.into()),
+				..SemanticTagPersonNameComponents::default()
+			});
+			Ok(())
+		})
+	}
+
+	// --- event tickets
+
+	#[must_use]
+	pub fn event_name(self, name: impl Into<String>) -> Self {
+		self.set("eventName", Some(Preset::EventTicket), |tags| {
+			tags.event_name = Some(name.into());
+			Ok(())
+		})
+	}
+
+	#[must_use]
+	pub fn event_type(self, event_type: SemanticEventType) -> Self {
+		self.set("eventType", Some(Preset::EventTicket), |tags| {
+			tags.event_type = Some(event_type);
+			Ok(())
+		})
+	}
+
+	#[must_use]
+	pub fn event_start_date(self, date: DateTime<Utc>) -> Self {
+		self.set("eventStartDate", Some(Preset::EventTicket), |tags| {
+			tags.event_start_date = Some(date);
+			Ok(())
+		})
+	}
+
+	#[must_use]
+	pub fn event_end_date(self, date: DateTime<Utc>) -> Self {
+		self.set("eventEndDate", Some(Preset::EventTicket), |tags| {
+			tags.event_end_date = Some(date);
+			Ok(())
+		})
+	}
+
+	#[must_use]
+	pub fn venue_name(self, name: impl Into<String>) -> Self {
+		self.set("venueName", Some(Preset::EventTicket), |tags| {
+			tags.venue_name = Some(name.into());
+			Ok(())
+		})
+	}
+
+	#[must_use]
+	pub fn venue_location(self, latitude: f64, longitude: f64) -> Self {
+		self.set("venueLocation", Some(Preset::EventTicket), |tags| {
+			tags.venue_location = Some(SemanticTagLocation {
+				latitude,
+				longitude,
+			});
+			Ok(())
+		})
+	}
+
+	/// Same coordinates as one of the relevant locations of the pass
+	#[must_use]
+	pub fn venue_location_from(self, location: &Location) -> Self {
+		self.venue_location(location.latitude, location.longitude)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use chrono::TimeZone;
+
+	#[test]
+	fn airline_boarding_pass_semantics() -> Result<()> {
+		let departure = Utc.with_ymd_and_hms(2024, 7, 22, 18, 30, 0).unwrap();
+
+		let semantics = SemanticTags::boarding_pass()
+			.flight_code("ex123")
+			.departure_airport_code("lhr")
+			.departure_location(51.47, -0.454)
+			.departure_date(departure)
+			.destination_airport_code("mpm")
+			.departure_gate("1A")
+			.passenger_name("Jane", "Doe")
+			.seat(SemanticTagSeat::new().number("12C"))
+			.build()?;
+
+		assert_eq!(
+			serde_json::to_value(&semantics)?,
+			serde_json::json!({
+				"airlineCode": "EX",
+				"departureAirportCode": "LHR",
+				"departureGate": "1A",
+				"departureLocation": { "latitude": 51.47, "longitude": -0.454 },
+				"destinationAirportCode": "MPM",
+				"flightCode": "EX123",
+				"flightNumber": 123,
+				"originalDepartureDate": "2024-07-22T18:30:00Z",
+				"passengerName": { "familyName": "Doe", "givenName": "Jane" },
+				"seats": [{ "seatNumber": "12C" }],
+			})
+		);
+
+		Ok(())
+	}
+
+	#[test]
+	fn locations_from_relevant_locations() -> std::result::Result<(), Box<dyn std::error::Error>> {
+		let venue = Location {
+			latitude: 48.8382,
+			longitude: 2.3783,
+			altitude: Some(35.),
+			relevant_text: Some("Doors open at 8pm".into()),
+		};
+
+		let semantics = SemanticTags::event_ticket()
+			.event_name("Concert")
+			.venue_location_from(&venue)
+			.build()?;
+		assert_eq!(
+			serde_json::to_value(&semantics.venue_location)?,
+			serde_json::json!({ "latitude": 48.8382, "longitude": 2.3783 })
+		);
+
+		let location = semantics.venue_location.as_ref().map(Location::from);
+		assert_eq!(
+			serde_json::to_value(location)?,
+			serde_json::json!({ "latitude": 48.8382, "longitude": 2.3783 })
+		);
+
+		Ok(())
+	}
+
+	#[test]
+	fn reject_tags_of_other_pass_types() {
+		let result = SemanticTags::boarding_pass()
+			.flight_code("EX123")
+			.event_name("Concert")
+			.build();
+		assert!(matches!(
+			result,
+			Err(Error::SemanticTagNotApplicable {
+				key: "eventName",
+				pass: "boarding pass"
+			})
+		));
+
+		let result = SemanticTags::event_ticket()
+			.event_name("Concert")
+			.departure_airport_code("LHR")
+			.build();
+		assert!(matches!(
+			result,
+			Err(Error::SemanticTagNotApplicable {
+				key: "departureAirportCode",
+				..
+			})
+		));
+
+		let result = SemanticTags::boarding_pass().flight_code("EX12345").build();
+		assert!(matches!(result, Err(Error::InvalidIataCode(_))));
+	}
+}
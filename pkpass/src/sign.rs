@@ -1,16 +1,28 @@
+#[cfg(all(feature = "sign", any(test, feature = "test-util")))]
+use openssl::x509::{store::X509StoreBuilder, X509PurposeId};
+#[cfg(feature = "apple")]
+use openssl::{error::ErrorStack, x509::X509NameRef};
+#[cfg(feature = "sign")]
 use openssl::{
-	pkcs12::ParsedPkcs12_2,
+	hash::MessageDigest,
+	nid::Nid,
+	pkcs12::{ParsedPkcs12_2, Pkcs12},
+	pkcs7::{Pkcs7, Pkcs7Flags},
 	pkey::{PKey, Private},
 	stack::Stack,
-	x509::X509,
+	x509::{store::X509Store, X509Ref, X509},
 };
-use std::{fmt, io, str::FromStr};
+use std::{fmt, str::FromStr};
+#[cfg(feature = "sign")]
+use std::{io, sync::Arc};
 
 #[derive(Debug)]
 pub struct Identity {
 	pub(crate) pass_type_id: String,
 	pub(crate) team_id: String,
+	#[cfg(feature = "sign")]
 	pub(crate) pen: Option<SigningPen>,
+	pub(crate) signer: Option<Box<dyn Signer>>,
 }
 
 impl Identity {
@@ -19,28 +31,45 @@ impl Identity {
 		Self {
 			pass_type_id,
 			team_id,
+			#[cfg(feature = "sign")]
 			pen: None,
+			signer: None,
 		}
 	}
 
+	/// Sign passes with a [`Signer`] holding the key outside of this process,
+	/// such as in an HSM
+	#[must_use]
+	pub fn with_signer(
+		pass_type_id: String,
+		team_id: String,
+		signer: impl Signer + 'static,
+	) -> Self {
+		Self {
+			pass_type_id,
+			team_id,
+			#[cfg(feature = "sign")]
+			pen: None,
+			signer: Some(Box::new(signer)),
+		}
+	}
+
+	/// Derive the pass type and team identifiers from an Apple-issued signer certificate.
+	///
+	/// # Errors
+	///
+	/// Fails if the certificate subject lacks the user id or organizational unit entries.
 	#[cfg(feature = "apple")]
 	pub fn from_apple_pen(pen: SigningPen) -> io::Result<Self> {
-		use openssl::nid::Nid;
-
-		let name = pen.signer_certificate.subject_name();
+		let (pass_type_id, team_id) = apple_identifiers(&pen.signer_certificate);
 
-		let get_entry = |nid: Nid| {
-			let op = name.entries_by_nid(nid).next();
-			Some(op?.data().as_utf8().ok()?.to_string())
-		};
-
-		let pass_type_id = get_entry(Nid::USERID).ok_or_else(|| {
+		let pass_type_id = pass_type_id.ok_or_else(|| {
 			io::Error::new(
 				io::ErrorKind::NotFound,
 				"could not find user id on apple cert",
 			)
 		})?;
-		let team_id = get_entry(Nid::ORGANIZATIONALUNITNAME).ok_or_else(|| {
+		let team_id = team_id.ok_or_else(|| {
 			io::Error::new(
 				io::ErrorKind::NotFound,
 				"could not find organization unit name on apple cert",
@@ -51,25 +80,154 @@ impl Identity {
 			pass_type_id,
 			team_id,
 			pen: Some(pen),
+			signer: None,
 		})
 	}
+
+	/// Generate an ephemeral self-signed identity and a store trusting it, for tests.
+	///
+	/// Passes written with it read back with [`VerifyMode::Custom`] and the
+	/// store, without any Apple certificate. The identifiers are
+	/// `pass.com.example.test` and `TEST123456`.
+	///
+	/// # Errors
+	///
+	/// Fails if openssl could not generate the key or the certificate.
+	#[cfg(all(feature = "sign", any(test, feature = "test-util")))]
+	pub fn test_self_signed() -> crate::Result<(Self, X509Store)> {
+		let (identity, cert) = crate::testing::self_signed("pass.com.example.test", "TEST123456")?;
+
+		let mut store = X509StoreBuilder::new()?;
+		store.add_cert(cert)?;
+		store.set_purpose(X509PurposeId::ANY)?;
+
+		Ok((identity, store.build()))
+	}
+
+	/// The pass type identifier filled in passes written with this identity
+	#[must_use]
+	pub fn pass_type_id(&self) -> &str {
+		&self.pass_type_id
+	}
+
+	/// The team identifier filled in passes written with this identity
+	#[must_use]
+	pub fn team_id(&self) -> &str {
+		&self.team_id
+	}
+
+	/// What signs the manifest, `None` for unsigned passes
+	pub(crate) fn signer(&self) -> Option<&dyn Signer> {
+		#[cfg(feature = "sign")]
+		if let Some(pen) = &self.pen {
+			return Some(pen);
+		}
+		self.signer.as_deref()
+	}
+}
+
+/// Sign the manifest of a pass
+///
+/// `SigningPen` signs with a private key in memory. Implement it when the
+/// key can't leave its storage, such as an HSM or a PKCS#11 token, and pass
+/// it to [`Identity::with_signer`].
+pub trait Signer: fmt::Debug + Send + Sync {
+	/// DER-encoded detached PKCS#7 signature of `manifest`, which Wallet
+	/// verifies with Apple's WWDR intermediate and root certificates
+	///
+	/// # Errors
+	///
+	/// Fails if the manifest could not be signed.
+	fn sign(&self, manifest: &[u8]) -> crate::Result<Vec<u8>>;
+}
+
+#[cfg(feature = "sign")]
+impl Signer for SigningPen {
+	fn sign(&self, manifest: &[u8]) -> crate::Result<Vec<u8>> {
+		self.check_key_pair()?;
+		#[cfg(feature = "apple")]
+		let implied_chain = self.implied_chain()?;
+		#[cfg(feature = "apple")]
+		let chain = implied_chain.as_ref().unwrap_or(&self.chain);
+		#[cfg(not(feature = "apple"))]
+		let chain = &self.chain;
+
+		let signature = Pkcs7::sign(
+			&self.signer_certificate,
+			&self.signer_private_key,
+			chain,
+			manifest,
+			self.flags,
+		)?;
+		Ok(signature.to_der()?)
+	}
 }
 
+/// Pass type identifier (user id) and team identifier (organizational unit) of an Apple-issued certificate
+#[cfg(feature = "sign")]
+pub(crate) fn apple_identifiers(cert: &X509Ref) -> (Option<String>, Option<String>) {
+	let name = cert.subject_name();
+
+	let get_entry = |nid: Nid| {
+		let op = name.entries_by_nid(nid).next();
+		op?.data().to_string().ok()
+	};
+
+	(
+		get_entry(Nid::USERID),
+		get_entry(Nid::ORGANIZATIONALUNITNAME),
+	)
+}
+
+/// Private key, certificate and chain of trust signing passes, with the `sign` feature
+#[cfg(feature = "sign")]
 pub struct SigningPen {
 	pub(crate) signer_private_key: PKey<Private>,
 	pub(crate) signer_certificate: X509,
 	pub(crate) chain: Stack<X509>,
+	pub(crate) flags: Pkcs7Flags,
 }
 
+#[cfg(feature = "sign")]
 impl fmt::Debug for SigningPen {
 	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
 		f.debug_struct("SigningPen").finish_non_exhaustive()
 	}
 }
 
+#[cfg(feature = "sign")]
 impl SigningPen {
+	/// Chain to embed in signatures instead of the one of the pen, when the
+	/// signer is issued by Apple's WWDR G4 intermediate which devices need to
+	/// verify it but the chain lacks it, such as an empty one
+	#[cfg(feature = "apple")]
+	pub(crate) fn implied_chain(&self) -> Result<Option<Stack<X509>>, ErrorStack> {
+		let wwdr = certificates::apple_wwdr_g4();
+		let is_wwdr = |name: &X509NameRef| -> Result<bool, ErrorStack> {
+			Ok(name.try_cmp(wwdr.subject_name())?.is_eq())
+		};
+
+		if !is_wwdr(self.signer_certificate.issuer_name())? {
+			return Ok(None);
+		}
+		for cert in &self.chain {
+			if is_wwdr(cert.subject_name())? {
+				return Ok(None);
+			}
+		}
+
+		let mut chain = Stack::new()?;
+		for cert in &self.chain {
+			chain.push(cert.to_owned())?;
+		}
+		chain.push(wwdr)?;
+		Ok(Some(chain))
+	}
+
+	/// Sign with `signer_private_key`, which must be the key of `signer_certificate`,
+	/// see [`SigningPen::check_key_pair`]
 	#[must_use]
-	pub fn new(
+	pub const fn new(
 		signer_private_key: PKey<Private>,
 		signer_certificate: X509,
 		chain: Stack<X509>,
@@ -78,39 +236,199 @@ impl SigningPen {
 			signer_private_key,
 			signer_certificate,
 			chain,
+			flags: Self::DEFAULT_FLAGS,
 		}
 	}
 
-	pub fn from_pkcs12(pkcs12: ParsedPkcs12_2) -> io::Result<Self> {
+	/// Flags used to sign the manifest unless changed with [`SigningPen::with_flags`]
+	///
+	/// Without `BINARY`, openssl signs the manifest as MIME text, turning line
+	/// feeds into CRLF before hashing. The signature then doesn't match the
+	/// manifest bytes stored in the archive as soon as they contain a newline.
+	pub const DEFAULT_FLAGS: Pkcs7Flags = Pkcs7Flags::DETACHED.union(Pkcs7Flags::BINARY);
+
+	/// Replace the PKCS#7 flags used to sign the manifest
+	///
+	/// `DETACHED` is always kept as the manifest is stored next to the signature.
+	#[must_use]
+	pub const fn with_flags(mut self, flags: Pkcs7Flags) -> Self {
+		self.flags = flags.union(Pkcs7Flags::DETACHED);
+		self
+	}
+
+	/// Parse a DER-encoded PKCS#12 archive, such as the one created by `pkp crypto bundle`
+	///
+	/// # Errors
+	///
+	/// Fails if the archive could not be decrypted with `password` or is missing
+	/// the private key or the certificate, see [`SigningPen::from_pkcs12`].
+	pub fn from_pkcs12_der(der: &[u8], password: &str) -> io::Result<Self> {
+		let invalid_data = |msg: &str| io::Error::new(io::ErrorKind::InvalidData, msg);
+
+		let pkcs12 = Pkcs12::from_der(der)
+			.map_err(|_| {
+				invalid_data("signing identity is not a valid DER-encoded PKCS#12 archive")
+			})?
+			.parse2(password)
+			.map_err(|_| invalid_data("could not decrypt PKCS#12 archive"))?;
+
+		Self::from_pkcs12(pkcs12)
+	}
+
+	/// Use the chain of trust of the archive, defaulting to Apple's WWDR G4
+	/// intermediate like `pkp crypto bundle` when the archive has none, as
+	/// exports often only contain the certificate and its private key.
+	///
+	/// # Errors
+	///
+	/// Fails if the archive is missing the private key or the certificate, or
+	/// the chain of trust without the `apple` feature.
+	pub fn from_pkcs12(mut pkcs12: ParsedPkcs12_2) -> io::Result<Self> {
+		let chain = pkcs12.ca.take().filter(|chain| !chain.is_empty());
+
+		#[cfg(feature = "apple")]
+		let chain = if let Some(chain) = chain {
+			chain
+		} else {
+			let mut chain = Stack::new().map_err(io::Error::other)?;
+			chain
+				.push(certificates::apple_wwdr_g4())
+				.map_err(io::Error::other)?;
+			chain
+		};
+		#[cfg(not(feature = "apple"))]
+		let Some(chain) = chain
+		else {
+			return Err(io::Error::new(
+				io::ErrorKind::InvalidInput,
+				"archive has no chain of trust, provide it with `SigningPen::from_pkcs12_with_chain`",
+			));
+		};
+
+		Self::from_pkcs12_with_chain(pkcs12, chain)
+	}
+
+	/// Use the private key and certificate of the archive with a chain of trust
+	/// provided separately, ignoring the one of the archive if any
+	///
+	/// # Errors
+	///
+	/// Fails if the archive is missing the private key or the certificate, or
+	/// if they don't match, see [`SigningPen::check_key_pair`].
+	pub fn from_pkcs12_with_chain(pkcs12: ParsedPkcs12_2, chain: Stack<X509>) -> io::Result<Self> {
 		let invalid_input = |msg: &str| io::Error::new(io::ErrorKind::InvalidInput, msg);
 
-		let signer_private_key = pkcs12
-			.pkey
-			.ok_or_else(|| invalid_input("archive has to contain a private key"))?;
-		let signer_certificate = pkcs12
-			.cert
-			.ok_or_else(|| invalid_input("archive has to contain a certificate"))?;
-		let chain = pkcs12
-			.ca
-			.ok_or_else(|| invalid_input("archive has to contain a chain of trust"))?;
+		let signer_private_key = pkcs12.pkey.ok_or_else(|| {
+			invalid_input(
+				"archive has no private key, export the certificate along with its private key",
+			)
+		})?;
+		let signer_certificate = pkcs12.cert.ok_or_else(|| {
+			invalid_input(
+				"archive has no certificate, export the pass type certificate issued by Apple",
+			)
+		})?;
 
-		Ok(Self {
-			signer_private_key,
-			signer_certificate,
-			chain,
-		})
+		let pen = Self::new(signer_private_key, signer_certificate, chain);
+		pen.check_key_pair()
+			.map_err(|err| io::Error::new(io::ErrorKind::InvalidInput, err))?;
+		Ok(pen)
+	}
+
+	/// Check the private key is the one of the signer certificate, otherwise
+	/// signatures are made but never verify
+	///
+	/// # Errors
+	///
+	/// Fails with [`crate::Error::KeyCertMismatch`] if the keys don't match.
+	pub fn check_key_pair(&self) -> crate::Result<()> {
+		let public_key = self.signer_certificate.public_key()?;
+		if public_key.public_eq(&self.signer_private_key) {
+			Ok(())
+		} else {
+			Err(crate::Error::KeyCertMismatch)
+		}
+	}
+
+	/// Generate an ephemeral PKCS#12 archive and its self-signed certificate, for tests.
+	///
+	/// The archive is DER-encoded without password and holds no chain of
+	/// trust, like most exports. The identifiers are `pass.com.example.test`
+	/// and `TEST123456`.
+	///
+	/// # Errors
+	///
+	/// Fails if openssl could not generate the key, the certificate or the archive.
+	#[cfg(any(test, feature = "test-util"))]
+	pub fn test_pkcs12_der() -> crate::Result<(Vec<u8>, X509)> {
+		Ok(crate::testing::pkcs12(
+			"pass.com.example.test",
+			"TEST123456",
+		)?)
 	}
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// How to check the signature of a pass when reading it
+///
+/// Every mode but `No` rejects unsigned passes with
+/// [`Error::MissingFile`](crate::Error::MissingFile).
+///
+/// Verification needs openssl, so only `No` exists without the `sign`
+/// feature, and the bundled Apple certificates, so `Yes` only exists with the
+/// `apple` feature.
+#[derive(Clone)]
 pub enum VerifyMode {
 	No,
 
 	// TODO: rename to `Apple` when other exist?
 	#[cfg(feature = "apple")]
 	Yes,
+
+	/// Trust the certificates of the given store instead of Apple's, such as
+	/// the one of [`Identity::test_self_signed`]
+	#[cfg(feature = "sign")]
+	Custom(Arc<X509Store>),
+
+	/// Only trust a signer certificate with one of the given SHA-256
+	/// fingerprints, whatever its issuer, see [`VerifyMode::fingerprint`]
+	///
+	/// The signature itself is still checked, unsigned passes are rejected.
+	#[cfg(feature = "sign")]
+	PinnedSigner(Vec<[u8; 32]>),
+}
+
+impl fmt::Debug for VerifyMode {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			Self::No => f.write_str("No"),
+
+			#[cfg(feature = "apple")]
+			Self::Yes => f.write_str("Yes"),
+
+			#[cfg(feature = "sign")]
+			Self::Custom(_) => f.write_str("Custom(..)"),
+			#[cfg(feature = "sign")]
+			Self::PinnedSigner(pins) => f.debug_tuple("PinnedSigner").field(pins).finish(),
+		}
+	}
 }
 
+/// Custom modes are equal when they share the same store
+impl PartialEq for VerifyMode {
+	fn eq(&self, other: &Self) -> bool {
+		match (self, other) {
+			#[cfg(feature = "sign")]
+			(Self::Custom(store), Self::Custom(other)) => Arc::ptr_eq(store, other),
+			#[cfg(feature = "sign")]
+			(Self::PinnedSigner(pins), Self::PinnedSigner(other)) => pins == other,
+			_ => self.as_str() == other.as_str(),
+		}
+	}
+}
+
+impl Eq for VerifyMode {}
+
+/// `Yes` with the `apple` feature, `No` without it
 impl Default for VerifyMode {
 	fn default() -> Self {
 		#[cfg(feature = "apple")]
@@ -121,28 +439,84 @@ impl Default for VerifyMode {
 }
 
 impl FromStr for VerifyMode {
-	type Err = std::io::Error;
+	type Err = crate::Error;
 	fn from_str(s: &str) -> Result<Self, Self::Err> {
 		match s {
 			"no" => Ok(Self::No),
 
 			#[cfg(feature = "apple")]
 			"yes" => Ok(Self::Yes),
+			#[cfg(not(feature = "apple"))]
+			"yes" => Err(crate::Error::UnsupportedFeature {
+				what: "verify mode `yes`",
+				feature: "apple",
+			}),
 
-			_ => Err(io::Error::new(io::ErrorKind::InvalidInput, "")),
+			_ => Err(crate::Error::InvalidVerifyMode(s.into())),
 		}
 	}
 }
 
-impl fmt::Display for VerifyMode {
-	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+impl VerifyMode {
+	/// Every mode available with the enabled features
+	pub const ALL: &'static [Self] = &[
+		Self::No,
+		#[cfg(feature = "apple")]
+		Self::Yes,
+	];
+
+	/// Name of the mode, as parsed by [`VerifyMode::from_str`]
+	///
+	/// `Custom` and `PinnedSigner` are named `custom` and `pinned` but cannot
+	/// be parsed, they need a store or fingerprints.
+	#[must_use]
+	pub const fn as_str(&self) -> &'static str {
 		match self {
-			Self::No => write!(f, "no"),
+			Self::No => "no",
 
 			#[cfg(feature = "apple")]
-			Self::Yes => write!(f, "yes"),
+			Self::Yes => "yes",
+
+			#[cfg(feature = "sign")]
+			Self::Custom(_) => "custom",
+			#[cfg(feature = "sign")]
+			Self::PinnedSigner(_) => "pinned",
 		}
 	}
+
+	/// SHA-256 fingerprint of `certificate`, to pin with [`VerifyMode::PinnedSigner`]
+	///
+	/// # Errors
+	///
+	/// Fails if OpenSSL cannot hash the certificate.
+	#[cfg(feature = "sign")]
+	pub fn fingerprint(certificate: &X509Ref) -> crate::Result<[u8; 32]> {
+		let digest = certificate.digest(MessageDigest::sha256())?;
+		let mut fingerprint = [0; 32];
+		fingerprint.copy_from_slice(&digest);
+		Ok(fingerprint)
+	}
+
+	/// Names of the modes available with the enabled features, comma separated
+	pub(crate) fn accepted() -> String {
+		Self::ALL
+			.iter()
+			.map(Self::as_str)
+			.collect::<Vec<_>>()
+			.join(", ")
+	}
+}
+
+impl From<VerifyMode> for &'static str {
+	fn from(mode: VerifyMode) -> Self {
+		mode.as_str()
+	}
+}
+
+impl fmt::Display for VerifyMode {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.write_str(self.as_str())
+	}
 }
 
 pub mod certificates {
@@ -182,3 +556,134 @@ pub mod certificates {
 		let _ = apple_wwdr_g4();
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	#[cfg(feature = "apple")]
+	use crate::testing;
+	use crate::Error;
+
+	#[test]
+	#[cfg(feature = "apple")]
+	fn identifiers_from_apple_pen() -> Result<(), Box<dyn std::error::Error>> {
+		let (identity, _) = testing::self_signed("pass.com.acme", "ACME123456")?;
+		let identity = Identity::from_apple_pen(identity.pen.expect("identity signs"))?;
+
+		assert_eq!(identity.pass_type_id(), "pass.com.acme");
+		assert_eq!(identity.team_id(), "ACME123456");
+
+		Ok(())
+	}
+
+	#[test]
+	#[cfg(feature = "sign")]
+	fn identifiers_from_bmp_strings() -> Result<(), Box<dyn std::error::Error>> {
+		use openssl::{asn1::Asn1Type, x509::X509NameBuilder};
+
+		// BMPString holds UTF-16BE, which must be decoded rather than read as bytes
+		let utf16 = |value: &str| value.chars().flat_map(|c| ['\0', c]).collect::<String>();
+		let mut name = X509NameBuilder::new()?;
+		name.append_entry_by_nid_with_type(
+			Nid::USERID,
+			&utf16("pass.com.acme"),
+			Asn1Type::BMPSTRING,
+		)?;
+		name.append_entry_by_nid_with_type(
+			Nid::ORGANIZATIONALUNITNAME,
+			&utf16("ACME123456"),
+			Asn1Type::BMPSTRING,
+		)?;
+		let mut cert = X509::builder()?;
+		cert.set_subject_name(&name.build())?;
+
+		let (pass_type_id, team_id) = apple_identifiers(&cert.build());
+		assert_eq!(pass_type_id.as_deref(), Some("pass.com.acme"));
+		assert_eq!(team_id.as_deref(), Some("ACME123456"));
+
+		Ok(())
+	}
+
+	#[test]
+	#[cfg(feature = "sign")]
+	fn pkcs12_with_separate_chain() -> Result<(), Box<dyn std::error::Error>> {
+		let (der, cert) = SigningPen::test_pkcs12_der()?;
+		let pkcs12 = Pkcs12::from_der(&der)?.parse2("")?;
+		assert!(pkcs12.ca.as_ref().map_or(true, |chain| chain.is_empty()));
+
+		let mut chain = Stack::new()?;
+		chain.push(cert.clone())?;
+		let pen = SigningPen::from_pkcs12_with_chain(pkcs12, chain)?;
+		assert_eq!(pen.signer_certificate, cert);
+		assert_eq!(pen.chain.len(), 1);
+
+		Ok(())
+	}
+
+	#[test]
+	#[cfg(feature = "apple")]
+	fn pkcs12_without_chain_defaults_to_wwdr() -> Result<(), Box<dyn std::error::Error>> {
+		let (der, _) = SigningPen::test_pkcs12_der()?;
+		let pen = SigningPen::from_pkcs12_der(&der, "")?;
+
+		let chain = pen.chain.iter().map(X509Ref::to_owned).collect::<Vec<_>>();
+		assert_eq!(chain, [certificates::apple_wwdr_g4()]);
+
+		Ok(())
+	}
+
+	#[test]
+	#[cfg(all(feature = "sign", not(feature = "apple")))]
+	fn pkcs12_without_chain_explains_how_to_provide_one() -> Result<(), Box<dyn std::error::Error>>
+	{
+		let (der, _) = SigningPen::test_pkcs12_der()?;
+		let err = SigningPen::from_pkcs12_der(&der, "").expect_err("chain is missing");
+		assert!(err.to_string().contains("from_pkcs12_with_chain"));
+
+		Ok(())
+	}
+
+	#[test]
+	fn parse_verify_mode() {
+		assert_eq!("no".parse::<VerifyMode>().ok(), Some(VerifyMode::No));
+		assert!(matches!(
+			"maybe".parse::<VerifyMode>(),
+			Err(Error::InvalidVerifyMode(mode)) if mode == "maybe"
+		));
+	}
+
+	#[test]
+	fn verify_mode_round_trip() -> crate::Result<()> {
+		for mode in VerifyMode::ALL {
+			assert_eq!(mode.to_string().parse::<VerifyMode>()?, *mode);
+			assert_eq!(<&str>::from(mode.clone()), mode.as_str());
+		}
+		Ok(())
+	}
+
+	#[test]
+	#[cfg(feature = "apple")]
+	fn parse_verify_mode_yes() {
+		assert_eq!("yes".parse::<VerifyMode>().ok(), Some(VerifyMode::Yes));
+		assert_eq!(VerifyMode::default(), VerifyMode::Yes);
+	}
+
+	#[test]
+	#[cfg(not(feature = "apple"))]
+	fn parse_verify_mode_yes_without_apple() {
+		let err = "yes"
+			.parse::<VerifyMode>()
+			.expect_err("apple feature is disabled");
+		assert_eq!(
+			err.to_string(),
+			"UnsupportedFeature: verify mode `yes` requires the `apple` feature"
+		);
+		assert_eq!(VerifyMode::default(), VerifyMode::No);
+
+		let err = "oui".parse::<VerifyMode>().expect_err("not a verify mode");
+		assert_eq!(
+			err.to_string(),
+			"InvalidVerifyMode: `oui` is not a verify mode, expected one of: no"
+		);
+	}
+}
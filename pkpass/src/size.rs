@@ -0,0 +1,85 @@
+//! Byte size of the files making up a pass
+//!
+//! Wallet has no documented hard limit but large passes are slow to download
+//! and may be rejected by mail clients, this helps finding what to shrink.
+
+use crate::Pass;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SizeReport {
+	/// Size of `pass.json`, before identifiers are filled in when writing
+	pub pass_json: usize,
+	/// Size of every image and localization file, largest first, by path in
+	/// the archive such as `logo@2x.png` or `fr.lproj/pass.strings`
+	pub assets: Vec<(String, usize)>,
+	/// Sum of `pass.json` and every asset, archive overhead and signature excluded
+	pub total: usize,
+}
+
+impl SizeReport {
+	/// Size of the files under a `.lproj` localization directory
+	#[must_use]
+	pub fn localizations(&self) -> usize {
+		self.assets
+			.iter()
+			.filter(|(path, _)| path.contains(".lproj/"))
+			.map(|(_, size)| size)
+			.sum()
+	}
+
+	/// The `n` largest assets
+	#[must_use]
+	pub fn largest(&self, n: usize) -> &[(String, usize)] {
+		&self.assets[..n.min(self.assets.len())]
+	}
+}
+
+impl Pass {
+	/// Summarize the byte size of each file of the pass
+	#[must_use]
+	pub fn size_breakdown(&self) -> SizeReport {
+		let pass_json = serde_json::to_vec(&self.metadata).map_or(0, |json| json.len());
+
+		let mut assets = self
+			.assets
+			.paths()
+			.into_iter()
+			.map(|(path, content)| (path, content.len()))
+			.collect::<Vec<_>>();
+		assets.sort_by(|(a_path, a), (b_path, b)| b.cmp(a).then_with(|| a_path.cmp(b_path)));
+
+		let total = pass_json + assets.iter().map(|(_, size)| size).sum::<usize>();
+
+		SizeReport {
+			pass_json,
+			assets,
+			total,
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::{testing, Pass};
+
+	#[test]
+	fn size_breakdown_sums_to_total() -> Result<(), Box<dyn std::error::Error>> {
+		let mut pass = Pass::new(testing::config());
+		pass.assets.images.icon.size_x1 = Some(vec![0; 100]);
+		pass.assets.images.icon.size_x2 = Some(vec![0; 400]);
+		pass.assets.images.logo.size_x1 = Some(vec![0; 50]);
+		pass.assets.get("fr".parse()?).strings = Some(vec![0; 20]);
+
+		let report = pass.size_breakdown();
+		assert_eq!(
+			report.largest(2),
+			[("icon@2x.png".into(), 400), ("icon.png".into(), 100)]
+		);
+		assert_eq!(report.localizations(), 20);
+		assert!(report.pass_json > 0);
+		assert_eq!(report.total, report.pass_json + 400 + 100 + 50 + 20);
+		assert_eq!(report.largest(10).len(), 4);
+
+		Ok(())
+	}
+}
@@ -0,0 +1,131 @@
+//! Assets read on demand while writing a pass
+//!
+//! Servers issuing many passes with the same images can keep them on disk or
+//! in an object store instead of loading them in every [`Pass`].
+
+use crate::{
+	models::{Image, Version},
+	sign::Identity,
+	Pass, Result, WriteOptions,
+};
+use std::{
+	borrow::Cow,
+	collections::HashSet,
+	fs,
+	io::{self, Seek, Write},
+	path::PathBuf,
+};
+
+/// Where to read the images of a pass from, see [`Pass::write_with_source`]
+pub trait AssetSource {
+	/// Read the asset at `path` in the archive, such as `logo@2x.png`
+	///
+	/// # Errors
+	///
+	/// Fails with [`io::ErrorKind::NotFound`] when the source doesn't have the
+	/// asset, which is then skipped. Any other error aborts writing the pass.
+	fn read(&self, path: &str) -> io::Result<Vec<u8>>;
+}
+
+/// Images stored as files of a directory, named like in the archive
+#[derive(Debug, Clone)]
+pub struct DirAssetSource {
+	dir: PathBuf,
+}
+
+impl DirAssetSource {
+	#[must_use]
+	pub fn new(dir: impl Into<PathBuf>) -> Self {
+		Self { dir: dir.into() }
+	}
+}
+
+impl AssetSource for DirAssetSource {
+	fn read(&self, path: &str) -> io::Result<Vec<u8>> {
+		fs::read(self.dir.join(path))
+	}
+}
+
+impl Pass {
+	/// Write the pass like [`Pass::write`], reading images missing from its
+	/// assets from `source` one at a time
+	///
+	/// Every image name and resolution is looked up, such as `logo.png` or
+	/// `strip@3x.png`. Localized assets are only taken from the pass.
+	///
+	/// # Errors
+	///
+	/// Fails if `source` fails otherwise than with [`io::ErrorKind::NotFound`],
+	/// or for the same reasons as [`Pass::write`].
+	pub fn write_with_source(
+		&self,
+		identity: &Identity,
+		source: &impl AssetSource,
+		writer: impl Write + Seek,
+	) -> Result<()> {
+		let paths = self.assets.paths();
+		let in_memory = paths
+			.iter()
+			.map(|(path, _)| path.clone())
+			.collect::<HashSet<_>>();
+
+		let from_source = Image::ALL
+			.into_iter()
+			.flat_map(|image| {
+				[Version::Standard, Version::Size2X, Version::Size3X]
+					.map(|version| format!("{}{version}.png", image.name()))
+			})
+			.filter(move |path| !in_memory.contains(path))
+			.filter_map(|path| match source.read(&path) {
+				Ok(content) => Some(Ok((path, Cow::Owned(content)))),
+				Err(e) if e.kind() == io::ErrorKind::NotFound => None,
+				Err(e) => Some(Err(e.into())),
+			});
+
+		let assets = paths
+			.into_iter()
+			.map(|(path, content)| Ok((path, Cow::Borrowed(content.as_slice()))))
+			.chain(from_source);
+		self.write_archive(identity, assets, writer, WriteOptions::default())?;
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{sign::VerifyMode, testing};
+	use std::{env, io::Cursor, process};
+
+	#[test]
+	fn write_images_from_dir() -> Result<()> {
+		let dir = env::temp_dir().join(format!("pkpass-source-{}", process::id()));
+		fs::create_dir_all(&dir)?;
+		fs::write(dir.join("icon.png"), b"shared icon")?;
+		fs::write(dir.join("logo@2x.png"), b"shared logo")?;
+
+		let mut pass = Pass::new(testing::config());
+		// assets of the pass win over the source
+		pass.assets.images.icon.size_x1 = Some(b"own icon".to_vec());
+		let identity = testing::identity();
+
+		let mut archive = Cursor::new(vec![]);
+		let written = pass.write_with_source(&identity, &DirAssetSource::new(&dir), &mut archive);
+		let _ = fs::remove_dir_all(&dir);
+		written?;
+
+		archive.set_position(0);
+		let read = Pass::read(archive, VerifyMode::No)?;
+		assert_eq!(
+			read.assets.images.icon.size_x1.as_deref(),
+			Some(&b"own icon"[..])
+		);
+		assert_eq!(
+			read.assets.images.logo.size_x2.as_deref(),
+			Some(&b"shared logo"[..])
+		);
+		assert!(read.assets.images.logo.size_x1.is_none());
+
+		Ok(())
+	}
+}
@@ -1,8 +1,10 @@
 use crate::{
 	models::{Assets, Metadata},
-	Pass,
+	Error, Pass,
 };
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Template {
@@ -17,6 +19,76 @@ impl Template {
 	pub fn render(self) -> Pass {
 		Pass::from_raw_parts(self.meta, Assets::default())
 	}
+
+	/// Render a pass with every `{{name}}` placeholder replaced by its binding,
+	/// leaving the template untouched to render it again
+	///
+	/// Placeholders are replaced in every string of the metadata, including
+	/// the serial number and field values. Templates have no assets yet.
+	///
+	/// # Errors
+	///
+	/// Fails with [`Error::UnboundVariable`] if a placeholder has no binding.
+	pub fn render_ref<K, V>(
+		&self,
+		bindings: impl IntoIterator<Item = (K, V)>,
+	) -> crate::Result<Pass>
+	where
+		K: Into<String>,
+		V: Into<String>,
+	{
+		let bindings = bindings
+			.into_iter()
+			.map(|(name, value)| (name.into(), value.into()))
+			.collect::<HashMap<String, String>>();
+
+		let mut meta = serde_json::to_value(&self.meta)?;
+		substitute_value(&mut meta, &bindings)?;
+
+		Ok(Pass::from_raw_parts(
+			serde_json::from_value(meta)?,
+			Assets::default(),
+		))
+	}
+}
+
+fn substitute_value(value: &mut Value, bindings: &HashMap<String, String>) -> crate::Result<()> {
+	match value {
+		Value::String(text) => *text = substitute(text, bindings)?,
+		Value::Array(values) => {
+			for value in values {
+				substitute_value(value, bindings)?;
+			}
+		}
+		Value::Object(map) => {
+			for value in map.values_mut() {
+				substitute_value(value, bindings)?;
+			}
+		}
+		Value::Null | Value::Bool(_) | Value::Number(_) => {}
+	}
+	Ok(())
+}
+
+/// Replace the `{{name}}` placeholders of `text`
+fn substitute(text: &str, bindings: &HashMap<String, String>) -> crate::Result<String> {
+	let mut rendered = String::with_capacity(text.len());
+	let mut rest = text;
+	while let Some((before, after)) = rest.split_once("{{") {
+		let Some((name, after)) = after.split_once("}}") else {
+			break;
+		};
+		let name = name.trim();
+		let value = bindings
+			.get(name)
+			.ok_or_else(|| Error::UnboundVariable(name.into()))?;
+
+		rendered.push_str(before);
+		rendered.push_str(value);
+		rest = after;
+	}
+	rendered.push_str(rest);
+	Ok(rendered)
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -33,10 +105,89 @@ mod tests {
 	#[test]
 	fn render_template() -> Result<(), Box<dyn std::error::Error>> {
 		// let tpl = include_str!("examples/template.ron");
-		let tpl = "";
+		let tpl = r#"Template(
+			variables: [],
+			meta: Metadata(
+				formatVersion: 1,
+				passTypeIdentifier: "",
+				teamIdentifier: "",
+				organizationName: "Acme Inc.",
+				description: "A templated pass",
+				serialNumber: "1",
+				generic: Some(Fields()),
+			),
+		)"#;
 		let tpl = ron::from_str::<Template>(tpl)?;
 
-		dbg!(tpl);
+		let pass = tpl.render();
+		assert_eq!(pass.metadata.organization_name, "Acme Inc.");
+
+		Ok(())
+	}
+
+	#[test]
+	fn render_keeps_grouping_identifier() -> Result<(), Box<dyn std::error::Error>> {
+		let tpl = r#"Template(
+			variables: [],
+			meta: Metadata(
+				formatVersion: 1,
+				passTypeIdentifier: "",
+				teamIdentifier: "",
+				organizationName: "Example Airways",
+				description: "Boarding pass for flight EX123",
+				serialNumber: "1",
+				boardingPass: Some(Fields(transitType: Some(PKTransitTypeAir))),
+				groupingIdentifier: Some("trip-42"),
+			),
+		)"#;
+		let tpl = ron::from_str::<Template>(tpl)?;
+
+		let pass = tpl.render();
+		assert_eq!(
+			pass.metadata.grouping_identifier.as_deref(),
+			Some("trip-42")
+		);
+
+		Ok(())
+	}
+
+	#[test]
+	fn render_ref_twice() -> Result<(), Box<dyn std::error::Error>> {
+		let tpl = r#"Template(
+			variables: [],
+			meta: Metadata(
+				formatVersion: 1,
+				passTypeIdentifier: "",
+				teamIdentifier: "",
+				organizationName: "Acme Inc.",
+				description: "Ticket for {{ name }}",
+				serialNumber: "ticket-{{serial}}",
+				eventTicket: Some(Fields(
+					primaryFields: [Field(key: "holder", value: "{{name}}")],
+				)),
+			),
+		)"#;
+		let tpl = ron::from_str::<Template>(tpl)?;
+
+		let jane = tpl.render_ref([("name", "Jane"), ("serial", "1")])?;
+		let john = tpl.render_ref([("name", "John"), ("serial", "2")])?;
+
+		assert_eq!(jane.metadata.serial_number(), "ticket-1");
+		assert_eq!(jane.metadata.description(), "Ticket for Jane");
+		assert_eq!(
+			jane.metadata
+				.fields()
+				.map(|field| &field.value[..])
+				.collect::<Vec<_>>(),
+			["Jane"]
+		);
+		assert_eq!(john.metadata.serial_number(), "ticket-2");
+		assert_eq!(john.metadata.description(), "Ticket for John");
+
+		assert!(matches!(
+			tpl.render_ref([("name", "Jane")]),
+			Err(Error::UnboundVariable(name)) if name == "serial"
+		));
 
 		Ok(())
 	}
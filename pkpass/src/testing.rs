@@ -0,0 +1,121 @@
+//! Fixtures shared by tests, and helpers to exercise the signing paths
+//! without real Apple certificates
+
+use crate::sign::Identity;
+#[cfg(feature = "sign")]
+use crate::sign::SigningPen;
+#[cfg(test)]
+use crate::{
+	models::{Fields, PassKind},
+	PassConfig,
+};
+#[cfg(feature = "sign")]
+use openssl::{
+	asn1::Asn1Time,
+	bn::BigNum,
+	error::ErrorStack,
+	hash::MessageDigest,
+	nid::Nid,
+	pkcs12::Pkcs12,
+	pkey::PKey,
+	rsa::Rsa,
+	stack::Stack,
+	x509::{X509Builder, X509NameBuilder, X509NameRef, X509},
+};
+
+/// Configuration of a generic pass, set `kind` for other styles
+#[cfg(test)]
+pub(crate) fn config() -> PassConfig {
+	PassConfig {
+		organization_name: "Acme Inc.".into(),
+		description: "A test pass".into(),
+		serial_number: "1234".into(),
+		kind: PassKind::Generic(Fields::default()),
+	}
+}
+
+/// Smallest `pass.json` of the identifiers of [`config`] and [`identity`],
+/// without any style, which [`Metadata`](crate::models::Metadata) accepts
+#[cfg(test)]
+pub(crate) fn minimal_pass_json() -> serde_json::Value {
+	serde_json::json!({
+		"formatVersion": 1,
+		"passTypeIdentifier": "pass.com.acme",
+		"teamIdentifier": "ACME123456",
+		"organizationName": "Acme Inc.",
+		"description": "A test pass",
+		"serialNumber": "1234",
+	})
+}
+
+/// Identity writing unsigned passes
+#[cfg(test)]
+pub(crate) fn identity() -> Identity {
+	Identity::new_no_signature("pass.com.acme".into(), "ACME123456".into())
+}
+
+/// Generate an ephemeral self-signed identity and its certificate, to be used as a trust anchor
+#[cfg(feature = "sign")]
+pub(crate) fn self_signed(
+	pass_type_id: &str,
+	team_id: &str,
+) -> Result<(Identity, X509), ErrorStack> {
+	issued_by(pass_type_id, team_id, None)
+}
+
+/// Generate an ephemeral identity whose certificate claims to be issued by
+/// `issuer`, but is actually self-signed
+#[cfg(feature = "sign")]
+pub(crate) fn issued_by(
+	pass_type_id: &str,
+	team_id: &str,
+	issuer: Option<&X509NameRef>,
+) -> Result<(Identity, X509), ErrorStack> {
+	let key = PKey::from_rsa(Rsa::generate(2048)?)?;
+
+	let name = {
+		let mut name = X509NameBuilder::new()?;
+		name.append_entry_by_nid(Nid::USERID, pass_type_id)?;
+		name.append_entry_by_nid(Nid::COMMONNAME, &format!("Pass Type ID: {pass_type_id}"))?;
+		name.append_entry_by_nid(Nid::ORGANIZATIONALUNITNAME, team_id)?;
+		name.build()
+	};
+
+	let cert = {
+		let mut cert = X509Builder::new()?;
+		cert.set_version(2)?;
+		cert.set_serial_number(&*BigNum::from_u32(1)?.to_asn1_integer()?)?;
+		cert.set_subject_name(&name)?;
+		cert.set_issuer_name(issuer.unwrap_or(&name))?;
+		cert.set_pubkey(&key)?;
+		cert.set_not_before(&*Asn1Time::days_from_now(0)?)?;
+		cert.set_not_after(&*Asn1Time::days_from_now(1)?)?;
+		cert.sign(&key, MessageDigest::sha256())?;
+		cert.build()
+	};
+
+	let pen = SigningPen::new(key, cert.clone(), Stack::new()?);
+	let identity = Identity {
+		pass_type_id: pass_type_id.into(),
+		team_id: team_id.into(),
+		pen: Some(pen),
+		signer: None,
+	};
+
+	Ok((identity, cert))
+}
+
+/// Generate an ephemeral PKCS#12 archive without password nor chain of trust,
+/// and its self-signed certificate
+#[cfg(feature = "sign")]
+pub(crate) fn pkcs12(pass_type_id: &str, team_id: &str) -> Result<(Vec<u8>, X509), ErrorStack> {
+	let (identity, cert) = self_signed(pass_type_id, team_id)?;
+	let pen = identity.pen.expect("identity signs");
+
+	let mut p12 = Pkcs12::builder();
+	p12.pkey(&pen.signer_private_key);
+	p12.cert(&cert);
+	p12.key_algorithm(Nid::UNDEF);
+	p12.cert_algorithm(Nid::UNDEF);
+	Ok((p12.build2("")?.to_der()?, cert))
+}